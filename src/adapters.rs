@@ -0,0 +1,551 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::iter::{FusedIterator, Take};
+use core::{mem, result};
+
+use crate::io::{Read, Result, Seek};
+
+use crate::{ReverseLines, ReverseLinesError};
+
+impl<R: Seek + Read> ReverseLines<R> {
+    /// Consume this `ReverseLines` and return an iterator yielding the raw
+    /// bytes of each line, without any UTF-8 decoding, starting with any
+    /// line already peeked.
+    pub fn bytes(self) -> ReverseLinesBytes<R> {
+        ReverseLinesBytes { inner: self }
+    }
+
+    /// Consume this `ReverseLines` and return a reader of the raw bytes of
+    /// each line, starting with any line already peeked, each followed by
+    /// `\n` in the encoding being read, such as to copy the lines elsewhere
+    /// with `std::io::copy`. Lines are read one at a time as they are needed,
+    /// and a line longer than the buffer being read into is returned over
+    /// several reads.
+    ///
+    /// With [`ReverseLines::keep_ends`], no `\n` is added, as each line
+    /// already has its own line ending. The last line of a reader which does
+    /// not end with one is then followed directly by the line before it.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///  use std::io;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reader = ReverseLines::new(file).unwrap().into_reader();
+    ///
+    ///  let mut reversed = Vec::new();
+    ///  io::copy(&mut reader, &mut reversed).unwrap();
+    ///  assert_eq!(reversed, b"UVWXYZ\nLMNOPQRST\nGHIJK\nABCDEF\n");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn into_reader(self) -> ReverseLinesReader<R> {
+        ReverseLinesReader {
+            inner: self,
+            line: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding at most the
+    /// last `n` lines, like `tail`.
+    ///
+    /// Lines are only read as they are yielded, so nothing before the `n`th
+    /// line from the end is read. Errors count towards the `n` lines, and
+    /// iteration stops early after an I/O error as usual.
+    pub fn take_lines(self, n: usize) -> Take<ReverseLines<R>> {
+        self.take(n)
+    }
+
+    /// Consume this `ReverseLines` and return at most the last `n` lines, in
+    /// their original order from top to bottom.
+    ///
+    /// Only the last `n` lines are read, and the first error encountered is
+    /// returned instead.
+    pub fn tail(self, n: usize) -> result::Result<Vec<String>, ReverseLinesError> {
+        let mut lines = self
+            .take(n)
+            .collect::<result::Result<Vec<String>, ReverseLinesError>>()?;
+        lines.reverse();
+
+        Ok(lines)
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding the lines
+    /// in batches of `n`, except for the last batch which may be smaller.
+    ///
+    /// An error is yielded in place of the batch being put together, and the
+    /// lines already in it are kept for the next batch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut chunks = ReverseLines::new(file).unwrap().chunks(3);
+    ///
+    ///  assert_eq!(chunks.next().unwrap().unwrap(), ["UVWXYZ", "LMNOPQRST", "GHIJK"]);
+    ///  assert_eq!(chunks.next().unwrap().unwrap(), ["ABCDEF"]);
+    ///  assert!(chunks.next().is_none());
+    /// ```
+    pub fn chunks(self, n: usize) -> ReverseLinesChunks<R> {
+        assert!(n != 0, "chunk size must not be zero");
+
+        ReverseLinesChunks {
+            inner: self,
+            size: n,
+            chunk: Vec::new(),
+        }
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding only the
+    /// lines whose raw bytes satisfy `filter`, like `grep`. Other lines are
+    /// skipped without decoding them.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file)
+    ///      .unwrap()
+    ///      .with_byte_filter(|line| line.contains(&b'K'));
+    ///
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "GHIJK");
+    ///  assert!(reverse_lines.next().is_none());
+    /// ```
+    pub fn with_byte_filter<F: FnMut(&[u8]) -> bool>(
+        self,
+        filter: F,
+    ) -> ReverseLinesFiltered<R, F> {
+        ReverseLinesFiltered {
+            inner: self,
+            filter,
+        }
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding only the
+    /// lines which are not empty, once their line ending has been stripped
+    /// and any whitespace trimmed as set with [`ReverseLines::trim`]. Empty
+    /// lines are skipped without decoding them.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/blank_line_file").unwrap();
+    ///  let lines = ReverseLines::new(file).unwrap().skip_blank();
+    ///
+    ///  let lines = lines.collect::<Result<Vec<_>, _>>().unwrap();
+    ///  assert_eq!(lines, ["XYZ", "ABCD"]);
+    /// ```
+    pub fn skip_blank(self) -> ReverseLinesNonBlank<R> {
+        ReverseLinesNonBlank { inner: self }
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding the result
+    /// of calling `f` on each line, such as to parse it. Errors are passed
+    /// through as they are, without calling `f`.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut lengths = ReverseLines::new(file).unwrap().map_lines(|line| line.len());
+    ///
+    ///  assert_eq!(lengths.next().unwrap().unwrap(), 6);
+    ///  assert_eq!(lengths.next().unwrap().unwrap(), 9);
+    /// ```
+    pub fn map_lines<T, F: FnMut(String) -> T>(self, f: F) -> ReverseLinesMapped<R, F> {
+        ReverseLinesMapped { inner: self, f }
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding each line
+    /// along with the byte offset of its first byte in the reader.
+    pub fn with_offsets(self) -> ReverseLinesWithOffsets<R> {
+        ReverseLinesWithOffsets { inner: self }
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding each line
+    /// along with its 1-based number counted from the end, so the last line
+    /// of the reader is numbered 1.
+    ///
+    /// Unlike `Iterator::enumerate`, errors are passed through as they are,
+    /// though a line which fails to decode still counts towards the numbers
+    /// of the lines after it.
+    pub fn enumerate_from_end(self) -> ReverseLinesEnumerated<R> {
+        ReverseLinesEnumerated {
+            inner: self,
+            count: 0,
+        }
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding each line
+    /// along with its 1-based line number counted from the top of the
+    /// reader, so the numbers descend to 1.
+    ///
+    /// The remaining lines are scanned once up front to count them, without
+    /// decoding them, and any error encountered while doing so is returned.
+    pub fn lines_with_numbers(mut self) -> Result<ReverseLinesNumbered<R>> {
+        let count = self.count_lines()?;
+
+        Ok(ReverseLinesNumbered { inner: self, count })
+    }
+
+    /// Consume this `ReverseLines` and return an iterator yielding the same
+    /// lines, but which knows exactly how many are left, such as for
+    /// progress bars needing `ExactSizeIterator::len`.
+    ///
+    /// The remaining lines are scanned once up front to count them, without
+    /// decoding them, and any error encountered while doing so is returned.
+    /// When iteration is not fused on errors, a line which failed with an I/O
+    /// error is still counted, as it is read again by the following call.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap().precount().unwrap();
+    ///
+    ///  assert_eq!(reverse_lines.len(), 4);
+    ///  reverse_lines.next();
+    ///  assert_eq!(reverse_lines.len(), 3);
+    /// ```
+    pub fn precount(mut self) -> Result<ReverseLinesCounted<R>> {
+        let remaining = self.count_lines()?;
+
+        Ok(ReverseLinesCounted {
+            inner: self,
+            remaining,
+        })
+    }
+}
+
+/// `ReverseLinesBytes` struct, yielding each line as raw bytes
+///
+/// This is created by [`ReverseLines::bytes`].
+pub struct ReverseLinesBytes<R: Seek + Read> {
+    inner: ReverseLines<R>,
+}
+
+impl<R: Read + Seek> Iterator for ReverseLinesBytes<R> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(peeked) = self.inner.take_peeked_bytes() {
+            return peeked.map(Ok);
+        }
+
+        self.inner.next_bytes()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<R: Read + Seek> FusedIterator for ReverseLinesBytes<R> {}
+
+/// `ReverseLinesMapped` struct, yielding the result of calling a closure on
+/// each line
+///
+/// This is created by [`ReverseLines::map_lines`].
+pub struct ReverseLinesMapped<R: Seek + Read, F> {
+    inner: ReverseLines<R>,
+    f: F,
+}
+
+impl<R: Read + Seek, T, F: FnMut(String) -> T> Iterator for ReverseLinesMapped<R, F> {
+    type Item = result::Result<T, ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.inner.next()?.map(&mut self.f))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<R: Read + Seek, T, F: FnMut(String) -> T> FusedIterator for ReverseLinesMapped<R, F> {}
+
+/// `ReverseLinesReader` struct, reading the lines as bytes
+///
+/// This is created by [`ReverseLines::into_reader`].
+#[cfg(feature = "std")]
+pub struct ReverseLinesReader<R: Seek + Read> {
+    inner: ReverseLines<R>,
+    line: Vec<u8>,
+    pos: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read + Seek> Read for ReverseLinesReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if self.pos == self.line.len() {
+            self.line.clear();
+            self.pos = 0;
+
+            let line = match self.inner.take_peeked_bytes() {
+                Some(peeked) => peeked.map(|line| {
+                    self.line = line;
+                    Ok(())
+                }),
+                None => self.inner.next_bytes_into(&mut self.line, false),
+            };
+            match line {
+                Some(Ok(())) if !self.inner.keep_ends => {
+                    let newline = self.inner.code_units.widen(b"\n");
+                    self.line.extend_from_slice(&newline);
+                }
+                Some(Ok(())) => (),
+                Some(Err(e)) => return Err(e),
+                None => return Ok(0),
+            }
+        }
+
+        let size = buf.len().min(self.line.len() - self.pos);
+        buf[..size].copy_from_slice(&self.line[self.pos..self.pos + size]);
+        self.pos += size;
+
+        Ok(size)
+    }
+}
+
+/// `ReverseLinesWithOffsets` struct, yielding each line along with its
+/// starting byte offset
+///
+/// This is created by [`ReverseLines::with_offsets`].
+pub struct ReverseLinesWithOffsets<R: Seek + Read> {
+    inner: ReverseLines<R>,
+}
+
+impl<R: Read + Seek> Iterator for ReverseLinesWithOffsets<R> {
+    type Item = result::Result<(u64, String), ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.inner.next()?;
+
+        Some(line.map(|line| (self.inner.line_start(), line)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<R: Read + Seek> FusedIterator for ReverseLinesWithOffsets<R> {}
+
+/// `ReverseLinesEnumerated` struct, yielding each line along with its number
+/// counted from the end
+///
+/// This is created by [`ReverseLines::enumerate_from_end`].
+pub struct ReverseLinesEnumerated<R: Seek + Read> {
+    inner: ReverseLines<R>,
+    count: usize,
+}
+
+impl<R: Read + Seek> Iterator for ReverseLinesEnumerated<R> {
+    type Item = result::Result<(usize, String), ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.inner.next()?;
+        self.count += 1;
+
+        Some(line.map(|line| (self.count, line)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<R: Read + Seek> FusedIterator for ReverseLinesEnumerated<R> {}
+
+/// `ReverseLinesNumbered` struct, yielding each line along with its line
+/// number counted from the top
+///
+/// This is created by [`ReverseLines::lines_with_numbers`].
+pub struct ReverseLinesNumbered<R: Seek + Read> {
+    inner: ReverseLines<R>,
+    count: usize,
+}
+
+impl<R: Read + Seek> Iterator for ReverseLinesNumbered<R> {
+    type Item = result::Result<(usize, String), ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.inner.next()?;
+        let number = self.count;
+        self.count = self.count.saturating_sub(1);
+
+        Some(line.map(|line| (number, line)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<R: Read + Seek> FusedIterator for ReverseLinesNumbered<R> {}
+
+/// `ReverseLinesChunks` struct, yielding the lines in batches
+///
+/// This is created by [`ReverseLines::chunks`].
+pub struct ReverseLinesChunks<R: Seek + Read> {
+    inner: ReverseLines<R>,
+    size: usize,
+    chunk: Vec<String>,
+}
+
+impl<R: Read + Seek> Iterator for ReverseLinesChunks<R> {
+    type Item = result::Result<Vec<String>, ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.chunk.len() < self.size {
+            match self.inner.next() {
+                Some(Ok(line)) => self.chunk.push(line),
+                Some(Err(e)) => return Some(Err(e)),
+                None => break,
+            }
+        }
+
+        if self.chunk.is_empty() {
+            return None;
+        }
+
+        Some(Ok(mem::take(&mut self.chunk)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.inner.size_hint();
+        let chunks = |lines: usize| lines.saturating_add(self.chunk.len()).div_ceil(self.size);
+
+        (chunks(lower), upper.map(chunks))
+    }
+}
+
+impl<R: Read + Seek> FusedIterator for ReverseLinesChunks<R> {}
+
+/// `ReverseLinesFiltered` struct, yielding only the lines whose raw bytes
+/// satisfy a predicate
+///
+/// This is created by [`ReverseLines::with_byte_filter`].
+pub struct ReverseLinesFiltered<R: Seek + Read, F> {
+    inner: ReverseLines<R>,
+    filter: F,
+}
+
+impl<R: Read + Seek, F: FnMut(&[u8]) -> bool> Iterator for ReverseLinesFiltered<R, F> {
+    type Item = result::Result<String, ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let filter = &mut self.filter;
+        next_filtered(&mut self.inner, |_, line| filter(line))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+impl<R: Read + Seek, F: FnMut(&[u8]) -> bool> FusedIterator for ReverseLinesFiltered<R, F> {}
+
+/// `ReverseLinesNonBlank` struct, yielding only the lines which are not
+/// empty
+///
+/// This is created by [`ReverseLines::skip_blank`].
+pub struct ReverseLinesNonBlank<R: Seek + Read> {
+    inner: ReverseLines<R>,
+}
+
+impl<R: Read + Seek> Iterator for ReverseLinesNonBlank<R> {
+    type Item = result::Result<String, ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_filtered(&mut self.inner, |inner, line| !inner.is_blank(line))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+impl<R: Read + Seek> FusedIterator for ReverseLinesNonBlank<R> {}
+
+// Read the next line whose raw bytes satisfy `filter`, skipping other lines
+// without decoding them
+fn next_filtered<R: Read + Seek, F: FnMut(&ReverseLines<R>, &[u8]) -> bool>(
+    inner: &mut ReverseLines<R>,
+    mut filter: F,
+) -> Option<result::Result<String, ReverseLinesError>> {
+    // A peeked line is filtered on its raw bytes, left in the scratch buffer
+    match inner.peeked.take() {
+        Some(Some(line)) if filter(inner, &inner.scratch) => return Some(Ok(line)),
+        Some(None) => return None,
+        _ => (),
+    }
+
+    let mut result = mem::take(&mut inner.scratch);
+    let line = loop {
+        result.clear();
+
+        match inner.next_bytes_into(&mut result, false) {
+            Some(Ok(())) if !filter(inner, &result) => continue,
+            Some(Ok(())) => break Some(inner.decode_slice(&result).map(Cow::into_owned)),
+            Some(Err(e)) => break Some(Err(e.into())),
+            None => break None,
+        }
+    };
+
+    inner.scratch = result;
+    line
+}
+
+/// `ReverseLinesCounted` struct, yielding each line while keeping count of
+/// how many are left
+///
+/// This is created by [`ReverseLines::precount`].
+pub struct ReverseLinesCounted<R: Seek + Read> {
+    inner: ReverseLines<R>,
+    remaining: usize,
+}
+
+impl<R: Seek + Read> ReverseLinesCounted<R> {
+    /// Unwrap the `ReverseLines` lines are being read from.
+    pub fn into_inner(self) -> ReverseLines<R> {
+        self.inner
+    }
+}
+
+impl<R: Read + Seek> Iterator for ReverseLinesCounted<R> {
+    type Item = result::Result<String, ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.inner.next();
+        match line {
+            None => self.remaining = 0,
+            // The line is only given up on once iteration stops
+            Some(Err(ReverseLinesError::Io(_))) if !self.inner.is_error => (),
+            Some(Err(ReverseLinesError::Io(_))) => self.remaining = 0,
+            Some(_) => self.remaining = self.remaining.saturating_sub(1),
+        }
+
+        line
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<R: Read + Seek> ExactSizeIterator for ReverseLinesCounted<R> {}
+
+impl<R: Read + Seek> FusedIterator for ReverseLinesCounted<R> {}
@@ -0,0 +1,321 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::future::poll_fn;
+use core::mem;
+use core::pin::Pin;
+use core::result;
+use core::task::{ready, Context, Poll};
+use std::io::{Error, ErrorKind, Result, SeekFrom};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt, ReadBuf};
+
+use crate::{ReverseLinesError, CR_BYTE, DEFAULT_SIZE, LF_BYTE};
+
+/// `AsyncReverseLines` struct, an asynchronous counterpart to
+/// [`ReverseLines`](crate::ReverseLines) for readers implementing tokio's
+/// `AsyncRead` and `AsyncSeek`
+///
+/// Lines are split on `\n` with a preceding `\r` stripped, as by default with
+/// `ReverseLines`.
+///
+/// #### Example
+///
+/// ```
+///  use reverse_lines::AsyncReverseLines;
+///  use std::io::Cursor;
+///
+///  # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+///  let mut reverse_lines = AsyncReverseLines::new(Cursor::new(b"a\nb\nc\n")).await.unwrap();
+///
+///  while let Some(line) = reverse_lines.next_line().await {
+///      println!("{}", line.unwrap());
+///  }
+///  # });
+/// ```
+pub struct AsyncReverseLines<R: AsyncRead + AsyncSeek + Unpin> {
+    reader: R,
+    reader_pos: u64,
+    buf_size: u64,
+    line_end: Vec<u8>,
+    buf: Vec<u8>,
+    buf_pos: u64,
+    read_state: ReadState,
+    partial: Option<PartialLine>,
+    is_error: bool,
+}
+
+// How far reading into the buffer has got, so that it can be resumed after
+// the reader returns `Poll::Pending`
+enum ReadState {
+    Idle,
+    Seeking,
+    Reading(usize),
+}
+
+// A line partway through being read, kept across `Poll::Pending`
+struct PartialLine {
+    // Stored backwards, as read
+    result: Vec<u8>,
+    strip_cr: bool,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncReverseLines<R> {
+    /// Create a new `AsyncReverseLines` struct from a `<R>`. Internal
+    /// buffering for iteration will default to 4096 bytes at a time.
+    pub async fn new(reader: R) -> Result<AsyncReverseLines<R>> {
+        AsyncReverseLines::with_capacity(DEFAULT_SIZE, reader).await
+    }
+
+    /// Create a new `AsyncReverseLines` struct from a `<R>`. Internal
+    /// buffering for iteration will use `cap` bytes at a time.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `cap` is zero.
+    pub async fn with_capacity(cap: usize, mut reader: R) -> Result<AsyncReverseLines<R>> {
+        if cap == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "capacity must not be zero",
+            ));
+        }
+
+        let reader_pos = reader.seek(SeekFrom::End(0)).await?;
+
+        let mut reverse_lines = AsyncReverseLines {
+            reader,
+            reader_pos,
+            buf_size: cap as u64,
+            line_end: Vec::new(),
+            buf: Vec::new(),
+            buf_pos: 0,
+            read_state: ReadState::Idle,
+            partial: None,
+            is_error: false,
+        };
+
+        // Skip the trailing terminator, as the final line is not empty
+        let end_size = min(reverse_lines.reader_pos, 2);
+        let end_buf = poll_fn(|cx| reverse_lines.poll_read_to_buffer(cx, end_size)).await?;
+
+        let mut strip_size = 0;
+        if end_buf.last() == Some(&LF_BYTE) {
+            strip_size += 1;
+
+            if end_buf.len() > 1 && end_buf[end_buf.len() - 2] == CR_BYTE {
+                strip_size += 1;
+            }
+        }
+
+        reverse_lines.line_end = end_buf[end_buf.len() - strip_size..].to_vec();
+        reverse_lines.reader_pos += (end_buf.len() - strip_size) as u64;
+        reverse_lines.buf = end_buf;
+
+        Ok(reverse_lines)
+    }
+
+    /// Read the next line, working backwards from the end of the reader.
+    /// Returns `None` once there are no lines left.
+    ///
+    /// Lines with invalid UTF-8 yield [`ReverseLinesError::Utf8`]. After an
+    /// I/O error, no more lines are read.
+    ///
+    /// This is cancel safe: if the future is dropped before it completes, a
+    /// line partway through being read is carried on with by the next call.
+    pub async fn next_line(&mut self) -> Option<result::Result<String, ReverseLinesError>> {
+        poll_fn(|cx| self.poll_next_line(cx)).await
+    }
+
+    /// Turn this into a [`Stream`] of the remaining lines, for use with
+    /// stream combinators.
+    ///
+    /// ```
+    ///  use reverse_lines::AsyncReverseLines;
+    ///  use std::io::Cursor;
+    ///
+    ///  # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    ///  let reverse_lines = AsyncReverseLines::new(Cursor::new(b"a\nb\nc\n")).await.unwrap();
+    ///  let stream = reverse_lines.into_stream();
+    ///  # });
+    /// ```
+    pub fn into_stream(self) -> ReverseLinesStream<R> {
+        ReverseLinesStream { lines: self }
+    }
+
+    /// Return the current byte offset into the reader. Everything before
+    /// this offset has yet to be read, and every line already yielded lies
+    /// after it.
+    pub fn position(&self) -> u64 {
+        self.reader_pos
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consume this `AsyncReverseLines`, returning the underlying reader.
+    /// Its position is unspecified.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn poll_next_line(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<result::Result<String, ReverseLinesError>>> {
+        if self.is_error {
+            return Poll::Ready(None);
+        }
+
+        match ready!(self.poll_next_bytes(cx)) {
+            Ok(Some(result)) => Poll::Ready(Some(
+                String::from_utf8(result).map_err(ReverseLinesError::Utf8),
+            )),
+            Ok(None) => Poll::Ready(None),
+            Err(e) => {
+                self.is_error = true;
+                self.read_state = ReadState::Idle;
+                self.partial = None;
+                Poll::Ready(Some(Err(e.into())))
+            }
+        }
+    }
+
+    // Read `size` bytes before the position, and move the position back
+    // over them. Bytes left over in the buffer from the last read are
+    // returned without reading them again; the buffer should be handed back
+    // by setting `self.buf` once done with. While the read is pending, the
+    // buffer is kept in `self.buf` with the position unchanged.
+    fn poll_read_to_buffer(&mut self, cx: &mut Context<'_>, size: u64) -> Poll<Result<Vec<u8>>> {
+        if let ReadState::Idle = self.read_state {
+            let buf_end = self.buf_pos + self.buf.len() as u64;
+            if self.buf_pos < self.reader_pos && self.reader_pos <= buf_end {
+                let mut buf = mem::take(&mut self.buf);
+                buf.truncate((self.reader_pos - self.buf_pos) as usize);
+                self.reader_pos = self.buf_pos;
+
+                return Poll::Ready(Ok(buf));
+            }
+
+            self.buf.clear();
+            self.buf.resize(size as usize, 0);
+
+            Pin::new(&mut self.reader).start_seek(SeekFrom::Start(self.reader_pos - size))?;
+            self.read_state = ReadState::Seeking;
+        }
+
+        if let ReadState::Seeking = self.read_state {
+            ready!(Pin::new(&mut self.reader).poll_complete(cx))?;
+            self.read_state = ReadState::Reading(0);
+        }
+
+        while let ReadState::Reading(filled) = self.read_state {
+            if filled == self.buf.len() {
+                break;
+            }
+
+            let mut read_buf = ReadBuf::new(&mut self.buf[filled..]);
+            ready!(Pin::new(&mut self.reader).poll_read(cx, &mut read_buf))?;
+
+            let read = read_buf.filled().len();
+            if read == 0 {
+                return Poll::Ready(Err(ErrorKind::UnexpectedEof.into()));
+            }
+            self.read_state = ReadState::Reading(filled + read);
+        }
+
+        self.read_state = ReadState::Idle;
+        self.reader_pos -= self.buf.len() as u64;
+        self.buf_pos = self.reader_pos;
+
+        Poll::Ready(Ok(mem::take(&mut self.buf)))
+    }
+
+    fn poll_next_bytes(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Vec<u8>>>> {
+        let mut partial = self.partial.take().unwrap_or_else(|| PartialLine {
+            result: Vec::new(),
+            strip_cr: self.line_end == [LF_BYTE],
+        });
+        let mut line_end: Vec<u8> = Vec::new();
+
+        'outer: loop {
+            if self.reader_pos == 0 {
+                // The first line may be empty, but still ends with a line
+                // ending, unless it has already been yielded
+                if partial.result.is_empty() && self.line_end.is_empty() {
+                    return Poll::Ready(Ok(None));
+                }
+
+                break;
+            }
+
+            let size = min(self.buf_size, self.reader_pos);
+            let buf = match self.poll_read_to_buffer(cx, size) {
+                Poll::Ready(buf) => buf?,
+                Poll::Pending => {
+                    self.partial = Some(partial);
+                    return Poll::Pending;
+                }
+            };
+
+            for (idx, ch) in buf.iter().enumerate().rev() {
+                // A CR before the LF ending this line is part of the line
+                // ending, but may not have been in the same buffer as the LF
+                if partial.strip_cr {
+                    partial.strip_cr = false;
+
+                    if *ch == CR_BYTE {
+                        self.line_end.insert(0, CR_BYTE);
+                        continue;
+                    }
+                }
+
+                if *ch == LF_BYTE {
+                    // Keep the rest of the buffer for the next line
+                    line_end.push(LF_BYTE);
+                    self.reader_pos += idx as u64;
+                    self.buf = buf;
+                    break 'outer;
+                }
+
+                partial.result.push(*ch);
+            }
+
+            // Keep the allocation, though none of the bytes are needed again
+            self.buf = buf;
+        }
+
+        // Reverse the results since they were written backwards
+        let mut result = partial.result;
+        result.reverse();
+        self.line_end = line_end;
+
+        Poll::Ready(Ok(Some(result)))
+    }
+}
+
+/// A [`Stream`] of the lines of an [`AsyncReverseLines`], created by
+/// [`AsyncReverseLines::into_stream`]
+///
+/// Lines are yielded exactly as by [`AsyncReverseLines::next_line`].
+pub struct ReverseLinesStream<R: AsyncRead + AsyncSeek + Unpin> {
+    lines: AsyncReverseLines<R>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> ReverseLinesStream<R> {
+    /// Get the `AsyncReverseLines` back. A line partway through being read
+    /// is carried on with by the next call to
+    /// [`AsyncReverseLines::next_line`].
+    pub fn into_inner(self) -> AsyncReverseLines<R> {
+        self.lines
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> Stream for ReverseLinesStream<R> {
+    type Item = result::Result<String, ReverseLinesError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().lines.poll_next_line(cx)
+    }
+}
@@ -0,0 +1,341 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::min;
+
+#[cfg(feature = "encoding")]
+use encoding_rs::Encoding;
+
+use crate::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
+use crate::{CodeUnits, LineEnding, ReverseLines, DEFAULT_SIZE, LF_BYTE};
+
+/// `ReverseLinesBuilder` struct, for configuring a [`ReverseLines`]
+///
+/// #### Example
+///
+/// ```
+///  use reverse_lines::ReverseLinesBuilder;
+///  use std::io::Cursor;
+///
+///  let cursor = Cursor::new(b"a\x1eb\x1ec");
+///  let reverse_lines = ReverseLinesBuilder::new()
+///      .capacity(2)
+///      .delimiter(0x1e)
+///      .build(cursor)
+///      .unwrap();
+///
+///  let lines: Vec<String> = reverse_lines.map(|line| line.unwrap()).collect();
+///  assert_eq!(lines, vec!["c", "b", "a"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReverseLinesBuilder {
+    capacity: usize,
+    delimiters: Vec<Vec<u8>>,
+    strip_cr: bool,
+    unicode_line_breaks: bool,
+    #[cfg(feature = "encoding")]
+    encoding: Option<&'static Encoding>,
+    lossy: bool,
+    keep_ends: bool,
+    trim_start: bool,
+    trim_end: bool,
+    skip_bom: bool,
+    fuse_on_error: bool,
+    max_line_len: Option<usize>,
+    range: Option<(u64, u64)>,
+    tail: Option<u64>,
+    skip_partial_line: bool,
+}
+
+impl ReverseLinesBuilder {
+    /// Create a new `ReverseLinesBuilder` with the default options: 4096
+    /// bytes of internal buffering, lines split on `\n` with a preceding
+    /// `\r` stripped, and strict UTF-8 decoding.
+    pub fn new() -> ReverseLinesBuilder {
+        let (delimiters, strip_cr) = LineEnding::Lf.delimiters();
+
+        ReverseLinesBuilder {
+            capacity: DEFAULT_SIZE,
+            delimiters,
+            strip_cr,
+            unicode_line_breaks: false,
+            #[cfg(feature = "encoding")]
+            encoding: None,
+            lossy: false,
+            keep_ends: false,
+            trim_start: false,
+            trim_end: false,
+            skip_bom: false,
+            fuse_on_error: true,
+            max_line_len: None,
+            range: None,
+            tail: None,
+            skip_partial_line: false,
+        }
+    }
+
+    /// Set the internal buffering for iteration to `cap` bytes at a time.
+    /// Building will fail with an error of kind `ErrorKind::InvalidInput` if
+    /// `cap` is zero.
+    pub fn capacity(mut self, cap: usize) -> ReverseLinesBuilder {
+        self.capacity = cap;
+        self
+    }
+
+    /// Split lines on `delimiter` instead of `\n`. A `\r` preceding the
+    /// delimiter is only stripped when the delimiter is `\n`.
+    pub fn delimiter(self, delimiter: u8) -> ReverseLinesBuilder {
+        self.byte_delimiter(&[delimiter])
+    }
+
+    /// Split lines on the byte sequence `delimiter`. Building will fail with
+    /// an error of kind `ErrorKind::InvalidInput` if `delimiter` is empty.
+    ///
+    /// Where occurrences of a delimiter which can overlap itself, such as
+    /// `\r\n\r\n`, do overlap, the one nearest the end is used when reading
+    /// in reverse, and the one nearest the start when reading forward.
+    pub fn byte_delimiter(mut self, delimiter: &[u8]) -> ReverseLinesBuilder {
+        self.delimiters = vec![delimiter.to_vec()];
+        self.strip_cr = delimiter == [LF_BYTE];
+        self
+    }
+
+    /// Split lines according to `line_ending`.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> ReverseLinesBuilder {
+        (self.delimiters, self.strip_cr) = line_ending.delimiters();
+        self
+    }
+
+    /// Set whether the Unicode line separators `U+2028 LINE SEPARATOR` and
+    /// `U+2029 PARAGRAPH SEPARATOR` also break lines. By default they do not.
+    ///
+    /// Building returns an error of kind `ErrorKind::InvalidInput` if this is
+    /// enabled and the text is in an encoding other than UTF-8 or UTF-16.
+    pub fn unicode_line_breaks(mut self, enabled: bool) -> ReverseLinesBuilder {
+        self.unicode_line_breaks = enabled;
+        self
+    }
+
+    /// Decode lines from `encoding` rather than UTF-8. Any encoding which
+    /// is compatible with ASCII, such as Windows-1252, is supported, as are
+    /// UTF-16LE and UTF-16BE; building will fail with an error of kind
+    /// `ErrorKind::InvalidInput` for any other.
+    ///
+    /// In Shift_JIS, Big5, EUC-KR, GBK and GB18030, the trail bytes of
+    /// multibyte characters may be ASCII, so building will also fail with
+    /// that error if any delimiter has a byte from `0` (0x30) up. Control
+    /// characters such as `\n`, spaces and the punctuation from `!` to `/`
+    /// are fine.
+    ///
+    /// Delimiters are still given as bytes, and for UTF-16 each byte is
+    /// taken to be an ASCII character and widened to two bytes, so `\n`
+    /// matches `U+000A` and so on. A byte order mark is only stripped with
+    /// [`ReverseLinesBuilder::skip_bom`].
+    #[cfg(feature = "encoding")]
+    pub fn encoding(mut self, encoding: &'static Encoding) -> ReverseLinesBuilder {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// See [`ReverseLines::lossy`].
+    pub fn lossy(mut self, lossy: bool) -> ReverseLinesBuilder {
+        self.lossy = lossy;
+        self
+    }
+
+    /// See [`ReverseLines::keep_ends`].
+    pub fn keep_ends(mut self, keep_ends: bool) -> ReverseLinesBuilder {
+        self.keep_ends = keep_ends;
+        self
+    }
+
+    /// See [`ReverseLines::trim`].
+    pub fn trim(self, trim: bool) -> ReverseLinesBuilder {
+        self.trim_start(trim).trim_end(trim)
+    }
+
+    /// See [`ReverseLines::trim_start`].
+    pub fn trim_start(mut self, trim_start: bool) -> ReverseLinesBuilder {
+        self.trim_start = trim_start;
+        self
+    }
+
+    /// See [`ReverseLines::trim_end`].
+    pub fn trim_end(mut self, trim_end: bool) -> ReverseLinesBuilder {
+        self.trim_end = trim_end;
+        self
+    }
+
+    /// See [`ReverseLines::skip_bom`].
+    pub fn skip_bom(mut self, skip_bom: bool) -> ReverseLinesBuilder {
+        self.skip_bom = skip_bom;
+        self
+    }
+
+    /// See [`ReverseLines::fuse_on_error`].
+    pub fn fuse_on_error(mut self, fuse_on_error: bool) -> ReverseLinesBuilder {
+        self.fuse_on_error = fuse_on_error;
+        self
+    }
+
+    /// See [`ReverseLines::max_line_len`].
+    pub fn max_line_len(mut self, limit: usize) -> ReverseLinesBuilder {
+        self.max_line_len = Some(limit);
+        self
+    }
+
+    /// Read only the lines within the byte range from `start` up to (but
+    /// excluding) `end`, rather than the whole reader. Building will fail
+    /// with an error of kind `ErrorKind::InvalidInput` if `start` is after
+    /// `end`, or `end` is past the end of the reader.
+    pub fn range(mut self, start: u64, end: u64) -> ReverseLinesBuilder {
+        self.range = Some((start, end));
+        self
+    }
+
+    /// Read only the last `n` bytes of the reader, or of the range if one
+    /// was given, so that nothing before them is ever read. The line at the
+    /// start of them may have been cut off, unless
+    /// [`skip_partial_line`](ReverseLinesBuilder::skip_partial_line) is set.
+    ///
+    /// Unlike with a range, the end is not fixed, so
+    /// [`ReverseLines::extend_to_end`] still finds lines appended later.
+    pub fn tail_bytes(mut self, n: u64) -> ReverseLinesBuilder {
+        self.tail = Some(n);
+        self
+    }
+
+    /// Set whether a line cut off by the start of the range or of the tail
+    /// being read is skipped, rather than yielded last. Only the bytes from
+    /// just before the start up to the end of the first line ending after it
+    /// are read to find out. By default the line is yielded.
+    pub fn skip_partial_line(mut self, skip_partial_line: bool) -> ReverseLinesBuilder {
+        self.skip_partial_line = skip_partial_line;
+        self
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>` with the configured
+    /// options.
+    pub fn build<R: Seek + Read>(&self, mut reader: R) -> Result<ReverseLines<R>> {
+        let code_units = self.check()?;
+
+        let (mut start, end) = match self.range {
+            Some((start, end)) => {
+                let reader_size = reader.seek(SeekFrom::End(0))?;
+                if start > end || end > reader_size {
+                    return Err(Error::new(ErrorKind::InvalidInput, "invalid range"));
+                }
+
+                (start, Some(end))
+            }
+            None => (0, None),
+        };
+
+        if let Some(n) = self.tail {
+            let end = match end {
+                Some(end) => end,
+                None => reader.seek(SeekFrom::End(0))?,
+            };
+
+            // Start on a character boundary, for encodings where characters
+            // are wider than a byte
+            let tail_start = end.saturating_sub(n);
+            if tail_start > start {
+                let size = code_units.size() as u64;
+                start = min(start + (tail_start - start).next_multiple_of(size), end);
+            }
+        }
+
+        let mut reverse_lines = self.build_unread(reader, start, end)?;
+
+        // Seek to end of reader now
+        reverse_lines.rewind()?;
+
+        if self.skip_partial_line {
+            reverse_lines.skip_partial_line()?;
+            reverse_lines.rewind()?;
+        }
+
+        Ok(reverse_lines)
+    }
+
+    // Check the options, and return the code units of the encoding
+    fn check(&self) -> Result<CodeUnits> {
+        if self.capacity == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "capacity must not be zero",
+            ));
+        }
+
+        if self.delimiters.iter().any(Vec::is_empty) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "delimiter must not be empty",
+            ));
+        }
+
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = self.encoding {
+            crate::encoding::check_delimiters(encoding, &self.delimiters)?;
+            return crate::encoding::code_units(encoding);
+        }
+
+        Ok(CodeUnits::Utf8)
+    }
+
+    // Create a `ReverseLines` with these options over the lines from `start`
+    // up to `end`, without reading or seeking. Iteration has to be set up
+    // afterwards, by rewinding or from a saved state.
+    pub(crate) fn build_unread<R: Seek + Read>(
+        &self,
+        reader: R,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<ReverseLines<R>> {
+        let code_units = self.check()?;
+
+        let mut delimiters: Vec<Vec<u8>> = self
+            .delimiters
+            .iter()
+            .map(|delimiter| code_units.widen(delimiter))
+            .collect();
+        if self.unicode_line_breaks {
+            delimiters.extend(code_units.unicode_line_breaks()?);
+        }
+
+        Ok(ReverseLines {
+            reader,
+            reader_pos: 0,
+            start,
+            end,
+            end_pos: 0,
+            front_pos: 0,
+            buf_size: self.capacity as u64,
+            delimiters,
+            strip_cr: self.strip_cr,
+            code_units,
+            #[cfg(feature = "encoding")]
+            encoding: self.encoding,
+            lossy: self.lossy,
+            keep_ends: self.keep_ends,
+            trim_start: self.trim_start,
+            trim_end: self.trim_end,
+            skip_bom: self.skip_bom,
+            fuse_on_error: self.fuse_on_error,
+            max_line_len: self.max_line_len,
+            line_end: Vec::new(),
+            gaps: Vec::new(),
+            peeked: None,
+            scratch: Vec::new(),
+            buf: Vec::new(),
+            buf_pos: 0,
+            is_error: false,
+        })
+    }
+}
+
+impl Default for ReverseLinesBuilder {
+    fn default() -> ReverseLinesBuilder {
+        ReverseLinesBuilder::new()
+    }
+}
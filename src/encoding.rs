@@ -0,0 +1,83 @@
+use alloc::borrow::Cow;
+use core::result;
+
+use encoding_rs::{Encoding, BIG5, EUC_KR, GB18030, GBK, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8};
+
+use crate::io::{Error, ErrorKind, Read, Result, Seek};
+use crate::{CodeUnits, ReverseLines, ReverseLinesBuilder, ReverseLinesError};
+
+// How `encoding` lays out text, or an error if line breaks can't be found in
+// it byte by byte
+pub(crate) fn code_units(encoding: &'static Encoding) -> Result<CodeUnits> {
+    if encoding == UTF_8 {
+        Ok(CodeUnits::Utf8)
+    } else if encoding == UTF_16LE {
+        Ok(CodeUnits::Utf16Le)
+    } else if encoding == UTF_16BE {
+        Ok(CodeUnits::Utf16Be)
+    } else if encoding.is_ascii_compatible() {
+        Ok(CodeUnits::Ascii)
+    } else {
+        Err(Error::new(ErrorKind::InvalidInput, "unsupported encoding"))
+    }
+}
+
+// Check that `delimiters` can't match part of a character in `encoding`.
+// The trail bytes of multibyte characters in these encodings may be ASCII,
+// from 0x40 up, or digits in the four byte sequences of GB18030.
+pub(crate) fn check_delimiters(encoding: &'static Encoding, delimiters: &[Vec<u8>]) -> Result<()> {
+    let has_ascii_trail_bytes = [SHIFT_JIS, BIG5, EUC_KR, GBK, GB18030].contains(&encoding);
+    if has_ascii_trail_bytes && delimiters.iter().flatten().any(|&byte| byte >= b'0') {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "delimiter may match part of a character in this encoding",
+        ));
+    }
+
+    Ok(())
+}
+
+impl<R: Seek + Read> ReverseLines<R> {
+    /// Create a new `ReverseLines` struct from a `<R>`, decoding lines from
+    /// `encoding` rather than UTF-8. Internal buffering for iteration will
+    /// default to 4096 bytes at a time.
+    ///
+    /// Lines are only decoded once they have been found whole, so encodings
+    /// which are not self-synchronizing, such as Shift_JIS, are decoded from
+    /// the start of each line. Returns an error of kind
+    /// `ErrorKind::InvalidInput` if line breaks cannot be found in
+    /// `encoding` byte by byte, as in ISO-2022-JP. See
+    /// [`ReverseLinesBuilder::encoding`] for which delimiters can be used.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::io::Cursor;
+    ///
+    ///  let cursor = Cursor::new(b"caf\xe9\r\n");
+    ///  let mut reverse_lines =
+    ///      ReverseLines::with_encoding(encoding_rs::WINDOWS_1252, cursor).unwrap();
+    ///
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "caf\u{e9}");
+    /// ```
+    pub fn with_encoding(encoding: &'static Encoding, reader: R) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new().encoding(encoding).build(reader)
+    }
+
+    pub(crate) fn decode_with<'a>(
+        &self,
+        encoding: &'static Encoding,
+        result: &'a [u8],
+    ) -> result::Result<Cow<'a, str>, ReverseLinesError> {
+        if self.lossy {
+            let (line, _) = encoding.decode_without_bom_handling(result);
+            return Ok(line);
+        }
+
+        encoding
+            .decode_without_bom_handling_and_without_replacement(result)
+            .ok_or_else(|| ReverseLinesError::Encoding {
+                encoding,
+                bytes: result.to_vec(),
+            })
+    }
+}
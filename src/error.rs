@@ -0,0 +1,103 @@
+use alloc::string::FromUtf8Error;
+#[cfg(feature = "encoding")]
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use crate::io::{self, ErrorKind};
+
+/// Errors yielded when reading lines
+///
+/// Errors from the reader are kept apart from lines which could not be
+/// decoded, so they can be told apart without inspecting the message. Either
+/// converts into an [`io::Error`], so `?` still works in functions
+/// returning [`io::Result`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReverseLinesError {
+    /// An I/O error from the reader, or a line longer than the maximum line
+    /// length. Iteration stops after this error while it is fused on errors,
+    /// as it is by default, and otherwise the same line is read again next.
+    Io(io::Error),
+    /// A line which is not valid UTF-8. Iteration may continue past it, and
+    /// the line's bytes can be taken from the error with
+    /// [`FromUtf8Error::into_bytes`].
+    Utf8(FromUtf8Error),
+    /// A line which is not valid in the encoding it is decoded from.
+    /// Iteration may continue past it.
+    #[cfg(feature = "encoding")]
+    Encoding {
+        /// The encoding the line was decoded from
+        encoding: &'static encoding_rs::Encoding,
+        /// The raw bytes of the line
+        bytes: Vec<u8>,
+    },
+}
+
+impl ReverseLinesError {
+    /// The bytes of the line which could not be decoded, as UTF-8 or the
+    /// encoding being read, or `None` for other errors.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match self {
+            ReverseLinesError::Utf8(e) => Some(e.as_bytes()),
+            #[cfg(feature = "encoding")]
+            ReverseLinesError::Encoding { bytes, .. } => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Convert into an [`io::Error`], as returned before errors were kept
+    /// apart. Lines which could not be decoded become errors of kind
+    /// `InvalidData`.
+    pub fn into_io_error(self) -> io::Error {
+        self.into()
+    }
+}
+
+impl fmt::Display for ReverseLinesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReverseLinesError::Io(e) => e.fmt(f),
+            ReverseLinesError::Utf8(e) => e.fmt(f),
+            #[cfg(feature = "encoding")]
+            ReverseLinesError::Encoding { encoding, .. } => {
+                write!(f, "line is not valid {}", encoding.name())
+            }
+        }
+    }
+}
+
+impl error::Error for ReverseLinesError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ReverseLinesError::Io(e) => Some(e),
+            ReverseLinesError::Utf8(e) => Some(e),
+            #[cfg(feature = "encoding")]
+            ReverseLinesError::Encoding { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReverseLinesError {
+    fn from(e: io::Error) -> ReverseLinesError {
+        ReverseLinesError::Io(e)
+    }
+}
+
+impl From<FromUtf8Error> for ReverseLinesError {
+    fn from(e: FromUtf8Error) -> ReverseLinesError {
+        ReverseLinesError::Utf8(e)
+    }
+}
+
+impl From<ReverseLinesError> for io::Error {
+    fn from(e: ReverseLinesError) -> io::Error {
+        match e {
+            ReverseLinesError::Io(e) => e,
+            #[cfg(feature = "std")]
+            e => io::Error::new(ErrorKind::InvalidData, e),
+            #[cfg(not(feature = "std"))]
+            _ => io::Error::new(ErrorKind::InvalidData, "line could not be decoded"),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{max, min};
+use core::result;
+
+use crate::io::{Read, Result, Seek, SeekFrom};
+use crate::{line_too_long, ReverseLines, ReverseLinesError, CR_BYTE, LF_BYTE};
+
+impl<R: Seek + Read> ReverseLines<R> {
+    /// Read the line after the current position in forward order, moving the
+    /// position past it. Returns `None` at the end of the reader, or of the
+    /// range being read.
+    ///
+    /// This works like a cursor: `next` yields the line before the position
+    /// and moves back over it, while `next_forward` yields the line after the
+    /// position and moves forward over it. So calling `next_forward` straight
+    /// after `next` yields the same line again.
+    pub fn next_forward(&mut self) -> Option<result::Result<String, ReverseLinesError>> {
+        let result = self.next_bytes_forward()?;
+
+        Some(
+            result
+                .map_err(ReverseLinesError::from)
+                .and_then(|result| self.decode(result)),
+        )
+    }
+
+    fn next_bytes_forward(&mut self) -> Option<Result<Vec<u8>>> {
+        if self.is_error {
+            return None;
+        }
+
+        match self.read_line_forward() {
+            Ok(Some(result)) => Some(Ok(result)),
+            Ok(None) => None,
+            Err(e) => {
+                self.is_error = self.fuse_on_error;
+                Some(Err(e))
+            }
+        }
+    }
+
+    fn read_line_forward(&mut self) -> Result<Option<Vec<u8>>> {
+        let end = match self.end {
+            Some(end) => end,
+            None => self.reader.seek(SeekFrom::End(0))?,
+        };
+
+        // The line after the position starts after its line ending
+        let line_start = self.reader_pos + self.line_end.len() as u64;
+        if line_start >= end {
+            return Ok(None);
+        }
+
+        let (mut result, line_end) = self.scan_forward(line_start, end)?;
+
+        // Leave the position between this line and its line ending, just as
+        // if it had been yielded by `next`
+        self.reader_pos = line_start + result.len() as u64;
+        self.line_end = line_end;
+
+        self.finish_line_forward(line_start, &self.line_end, &mut result);
+
+        Ok(Some(result))
+    }
+
+    pub(crate) fn next_bytes_back(&mut self) -> Option<Result<Vec<u8>>> {
+        if self.is_error {
+            return None;
+        }
+
+        match self.read_line_back() {
+            Ok(Some(result)) => Some(Ok(result)),
+            Ok(None) => None,
+            Err(e) => {
+                self.is_error = self.fuse_on_error;
+                Some(Err(e))
+            }
+        }
+    }
+
+    // Read the first line not yet yielded, from the front. The lines left
+    // lie between the front and the position, followed by the line ending
+    // found when moving the position, and are empty once that is too
+    fn read_line_back(&mut self) -> Result<Option<Vec<u8>>> {
+        // Once the lines from before those already yielded when the reader
+        // grew have all been read, carry on with the lines appended
+        while self
+            .gaps
+            .first()
+            .is_some_and(|gap| gap.start == self.front_pos && gap.line_end.is_empty())
+        {
+            self.front_pos = self.gaps.remove(0).end;
+        }
+
+        // Lines are read up to those already yielded, if the reader grew
+        let back_pos = self.gaps.first().map_or(self.reader_pos, |gap| gap.start);
+        let line_start = self.front_pos;
+        if line_start == back_pos && self.back_line_end().is_empty() {
+            return Ok(None);
+        }
+
+        let cr = self.code_units.widen(&[CR_BYTE]);
+        let (mut result, mut line_end) = self.scan_forward(line_start, back_pos)?;
+
+        self.front_pos = line_start + (result.len() + line_end.len()) as u64;
+
+        // The last line ends with the line ending after the position, which
+        // a CR before it may be part of
+        let followed_by_lf = self.strip_cr && self.code_units.is(self.back_line_end(), LF_BYTE);
+        if self.front_pos == back_pos && (line_end.is_empty() || (followed_by_lf && line_end == cr))
+        {
+            if line_end.is_empty() && followed_by_lf && result.ends_with(&cr) {
+                result.truncate(result.len() - cr.len());
+                line_end = cr;
+            }
+
+            line_end.append(self.back_line_end());
+        }
+
+        self.finish_line_forward(line_start, &line_end, &mut result);
+
+        Ok(Some(result))
+    }
+
+    // The line ending after the last line to be read from the front
+    fn back_line_end(&mut self) -> &mut Vec<u8> {
+        match self.gaps.first_mut() {
+            Some(gap) => &mut gap.line_end,
+            None => &mut self.line_end,
+        }
+    }
+
+    // Move the start forward to that of the first line starting within the
+    // range, or to the end if there is none, skipping a line cut off by it
+    pub(crate) fn skip_partial_line(&mut self) -> Result<()> {
+        let start = self.start;
+        if start == 0 {
+            return Ok(());
+        }
+
+        // Scan from far enough back to find a line ending which ends at the
+        // start, or straddles it
+        let size = self.code_units.size() as u64;
+        let mut pos =
+            start.saturating_sub((self.max_delimiter_size() as u64).next_multiple_of(size));
+        self.start = pos;
+
+        let max_line_len = self.max_line_len.take();
+        let scanned = loop {
+            match self.scan_forward(pos, self.end_pos) {
+                Ok((result, line_end)) => {
+                    pos += (result.len() + line_end.len()) as u64;
+                    if line_end.is_empty() || pos >= start {
+                        break Ok(());
+                    }
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        self.max_line_len = max_line_len;
+
+        self.start = max(pos, start);
+        scanned
+    }
+
+    // Read the line starting at `line_start`, up to `end` at most, returning
+    // it along with its line ending, which is empty if none was found
+    fn scan_forward(&mut self, line_start: u64, end: u64) -> Result<(Vec<u8>, Vec<u8>)> {
+        let mut result: Vec<u8> = Vec::new();
+        let mut line_end: Vec<u8> = Vec::new();
+        let mut next_char: Vec<u8> = Vec::new();
+        let cr = self.code_units.widen(&[CR_BYTE]);
+        let mut pos = line_start;
+
+        // The most recently read bytes may yet turn out to be part of a
+        // delimiter, so allow for them when enforcing the line length limit
+        let partial_size = self.partial_delimiter_size()?;
+        let max_result_size = self
+            .max_line_len
+            .map(|limit| limit.saturating_add(partial_size));
+
+        self.reader.seek(SeekFrom::Start(pos))?;
+
+        'outer: while pos < end {
+            let size = min(self.buf_size, end - pos);
+            let mut buf = vec![0; size as usize];
+
+            self.reader.read_exact(&mut buf)?;
+            pos += size;
+
+            for ch in buf {
+                // A CR line ending has been found, which an LF may follow
+                if !line_end.is_empty() {
+                    next_char.push(ch);
+                    if next_char.len() < self.code_units.size() {
+                        continue;
+                    }
+
+                    if self.code_units.is(&next_char, LF_BYTE) {
+                        line_end.append(&mut next_char);
+                    }
+
+                    break 'outer;
+                }
+
+                result.push(ch);
+
+                let found = self.delimiters.iter().find(|delimiter| {
+                    result.ends_with(delimiter)
+                        && self.is_aligned(line_start + (result.len() - delimiter.len()) as u64)
+                });
+
+                if let Some(delimiter) = found {
+                    result.truncate(result.len() - delimiter.len());
+                    line_end.extend_from_slice(delimiter);
+
+                    if self.strip_cr
+                        && self.code_units.is(delimiter, LF_BYTE)
+                        && result.ends_with(&cr)
+                    {
+                        result.truncate(result.len() - cr.len());
+                        line_end.splice(0..0, cr.iter().copied());
+                    }
+
+                    // When breaking on both CR and LF, a CR followed by an LF
+                    // is one line ending, so look at the next character first
+                    if !(self.strip_cr && delimiter == &cr) {
+                        break 'outer;
+                    }
+                }
+
+                if max_result_size.is_some_and(|size| result.len() > size) {
+                    return Err(line_too_long());
+                }
+            }
+        }
+
+        if self.max_line_len.is_some_and(|limit| result.len() > limit) {
+            return Err(line_too_long());
+        }
+
+        Ok((result, line_end))
+    }
+
+    // Finish a line read forward, which ends with `line_end`
+    fn finish_line_forward(&self, line_start: u64, line_end: &[u8], result: &mut Vec<u8>) {
+        if line_start == 0 {
+            self.strip_bom(result);
+        }
+
+        self.trim_whitespace(result);
+
+        if self.keep_ends {
+            result.extend_from_slice(line_end);
+        }
+    }
+}
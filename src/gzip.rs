@@ -0,0 +1,26 @@
+use std::io::{Cursor, Read, Result};
+
+use flate2::read::MultiGzDecoder;
+
+use crate::{ReverseLines, ReverseLinesBuilder};
+
+impl ReverseLines<Cursor<Vec<u8>>> {
+    /// Create a new `ReverseLines` struct over a gzip-compressed reader,
+    /// such as an archived log file, with the default options. As a
+    /// compressed stream cannot be read backwards, the whole of it is
+    /// decompressed into memory first. Streams made of several gzip members
+    /// one after another, as left by appending to a `.gz` file, are read in
+    /// full.
+    ///
+    /// Other options can be set by decompressing into a `Cursor` and passing
+    /// it to [`ReverseLinesBuilder::build`].
+    ///
+    /// Returns an error if the reader could not be read or is not valid
+    /// gzip.
+    pub fn from_gzip<G: Read>(reader: G) -> Result<ReverseLines<Cursor<Vec<u8>>>> {
+        let mut bytes = Vec::new();
+        MultiGzDecoder::new(reader).read_to_end(&mut bytes)?;
+
+        ReverseLinesBuilder::new().build(Cursor::new(bytes))
+    }
+}
@@ -0,0 +1,92 @@
+use alloc::vec::Vec;
+
+use crate::io::{Error, ErrorKind, Read, Result, Seek};
+use crate::ReverseLines;
+
+/// `LineIndex` struct, holding the offset of every line in a reader so that
+/// any line can be jumped to without scanning for it
+///
+/// This is created by [`ReverseLines::line_index`], and used with
+/// [`ReverseLines::seek_to_line`]. Lines are numbered from 0 at the top.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineIndex {
+    offsets: Vec<u64>,
+    end: u64,
+}
+
+impl LineIndex {
+    /// Return the number of lines in the index.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Return whether the index has no lines.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Return the byte offset of the first byte of `line`, or `None` if
+    /// there is no such line.
+    pub fn offset(&self, line: usize) -> Option<u64> {
+        self.offsets.get(line).copied()
+    }
+
+    /// Return the byte offsets of the first byte of every line, in
+    /// ascending order.
+    pub fn offsets(&self) -> &[u64] {
+        &self.offsets
+    }
+}
+
+impl<R: Seek + Read> ReverseLines<R> {
+    /// Scan the reader, or the range being read, once to build an index of
+    /// its lines, as with [`ReverseLines::line_offsets`]. Iteration restarts
+    /// from the end afterwards.
+    pub fn line_index(&mut self) -> Result<LineIndex> {
+        let offsets = self.line_offsets()?;
+
+        Ok(LineIndex {
+            offsets,
+            end: self.end_pos,
+        })
+    }
+
+    /// Move the position to the end of `line`, using an index built from the
+    /// same reader with the same options. From there, `next` yields `line`
+    /// and then the lines before it, while
+    /// [`next_forward`](ReverseLines::next_forward) yields the line after it.
+    ///
+    /// Only the line ending of `line` is read. Any peeked line is discarded,
+    /// and iteration continues even after an error, as with
+    /// [`ReverseLines::rewind`]. Returns an error of kind
+    /// `ErrorKind::InvalidInput` if the index has no such line.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///  let index = reverse_lines.line_index().unwrap();
+    ///
+    ///  reverse_lines.seek_to_line(&index, 1).unwrap();
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "GHIJK");
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "ABCDEF");
+    /// ```
+    pub fn seek_to_line(&mut self, index: &LineIndex, line: usize) -> Result<()> {
+        if line >= index.len() {
+            return Err(Error::new(ErrorKind::InvalidInput, "line out of range"));
+        }
+
+        self.is_error = false;
+        self.peeked = None;
+        self.front_pos = self.start;
+        self.gaps.clear();
+
+        // The line ends where the next one starts, and its line ending is
+        // found just as the last line's is when starting from the end
+        self.buf.clear();
+        self.reader_pos = index.offset(line + 1).unwrap_or(index.end);
+        self.skip_trailing_terminator()
+    }
+}
@@ -0,0 +1,123 @@
+//! The I/O traits and types readers are used through
+//!
+//! With the `std` feature, which is enabled by default, these are simply those
+//! of `std::io`. Without it, they are minimal stand-ins so that only `alloc`
+//! is needed, and readers implement [`Read`] and [`Seek`] from here instead.
+//! The `embedded-io` feature implements both for any reader implementing
+//! `embedded_io::Read` and `embedded_io::Seek`.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::*;
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use core::fmt;
+
+    /// The kinds of error that can occur, a subset of those of `std::io`
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        /// A parameter was incorrect
+        InvalidInput,
+        /// Data was invalid, such as a line being too long
+        InvalidData,
+        /// The reader ended before enough bytes were read
+        UnexpectedEof,
+        /// Any other error from the reader
+        Other,
+    }
+
+    /// An I/O error, consisting of its kind and a description
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: &'static str,
+    }
+
+    impl Error {
+        /// Create a new error of kind `kind`, described by `message`.
+        pub fn new(kind: ErrorKind, message: &'static str) -> Error {
+            Error { kind, message }
+        }
+
+        /// Return the kind of this error.
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    /// A specialised `Result` type for I/O operations
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Positions to seek to, as with `std::io::SeekFrom`
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum SeekFrom {
+        /// An offset from the start
+        Start(u64),
+        /// An offset from the end
+        End(i64),
+        /// An offset from the current position
+        Current(i64),
+    }
+
+    /// Readers of bytes, as with `std::io::Read`
+    pub trait Read {
+        /// Read exactly enough bytes to fill `buf`.
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<()>;
+    }
+
+    /// Readers which can seek, as with `std::io::Seek`
+    pub trait Seek {
+        /// Seek to `pos`, returning the new offset from the start.
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+    }
+
+    #[cfg(feature = "embedded-io")]
+    fn from_embedded(e: impl embedded_io::Error) -> Error {
+        match e.kind() {
+            embedded_io::ErrorKind::InvalidInput => {
+                Error::new(ErrorKind::InvalidInput, "invalid input to reader")
+            }
+            embedded_io::ErrorKind::InvalidData => {
+                Error::new(ErrorKind::InvalidData, "invalid data in reader")
+            }
+            _ => Error::new(ErrorKind::Other, "reader error"),
+        }
+    }
+
+    #[cfg(feature = "embedded-io")]
+    impl<T: embedded_io::Read> Read for T {
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+            embedded_io::Read::read_exact(self, buf).map_err(|e| match e {
+                embedded_io::ReadExactError::UnexpectedEof => {
+                    Error::new(ErrorKind::UnexpectedEof, "failed to fill whole buffer")
+                }
+                embedded_io::ReadExactError::Other(e) => from_embedded(e),
+            })
+        }
+    }
+
+    #[cfg(feature = "embedded-io")]
+    impl<T: embedded_io::Seek> Seek for T {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            let pos = match pos {
+                SeekFrom::Start(offset) => embedded_io::SeekFrom::Start(offset),
+                SeekFrom::End(offset) => embedded_io::SeekFrom::End(offset),
+                SeekFrom::Current(offset) => embedded_io::SeekFrom::Current(offset),
+            };
+
+            embedded_io::Seek::seek(self, pos).map_err(from_embedded)
+        }
+    }
+}
@@ -12,12 +12,11 @@
 //!  extern crate reverse_lines;
 //!
 //!  use reverse_lines::ReverseLines;
-//!  use std::io::BufReader;
 //!  use std::fs::File;
 //!
 //!  fn main() {
 //!      let file = File::open("tests/multi_line_file").unwrap();
-//!      let reverse_lines = ReverseLines::new(BufReader::new(file)).unwrap();
+//!      let reverse_lines = ReverseLines::new(file).unwrap();
 //!
 //!      for line in reverse_lines {
 //!          println!("{}", line.unwrap());
@@ -25,31 +24,239 @@
 //!  }
 //! ```
 //!
+//! Lines are counted the same way as by `BufRead::lines`: a line terminator at the very end does
+//! not start another line, so `"a\n"` is one line, while `"\n"` is one empty line and `"\n\n"`
+//! is two.
+//!
+//! Reads are buffered internally, so a `File` is best passed as it is. Wrapping it in a
+//! `BufReader` works, but only copies every line once more, as the `BufReader` has to discard
+//! its buffer on each seek.
+//!
+//! Options such as the buffer capacity, line delimiter and UTF-8 handling can be configured with
+//! `ReverseLinesBuilder`.
+//!
 //! If a line with invalid UTF-8 is encountered, or if there is an I/O error, the iterator will
-//! yield an `std::io::Error`.
+//! yield a `ReverseLinesError` saying which, which can be converted into an `std::io::Error`.
+//! Invalid UTF-8 can instead be replaced with `U+FFFD` by using
+//! `ReverseLines::new_lossy`, or lines can be read as raw bytes without any decoding by using
+//! `ReverseLines::bytes`.
+//!
+//! With the `encoding` feature enabled, lines in other encodings such as Windows-1252 or UTF-16 can
+//! be decoded by passing an `encoding_rs::Encoding` to `ReverseLinesBuilder::encoding`.
 //!
 //! This method uses logic borrowed from [uutils/coreutils
 //! tail](https://github.com/uutils/coreutils/blob/f2166fed0ad055d363aedff6223701001af090d3/src/tail/tail.rs#L399-L402)
 //! and code borrowed from [rev_lines](https://docs.rs/rev_lines/latest/rev_lines/).
 
-use std::cmp::min;
-use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
-use std::iter::FusedIterator;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{max, min};
+use core::fmt;
+use core::iter::FusedIterator;
+use core::mem;
+use core::result;
+
+use io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
 
 #[cfg(test)]
 #[macro_use]
 extern crate assert_matches;
 
+mod adapters;
+#[cfg(feature = "tokio")]
+mod async_reader;
+mod builder;
+#[cfg(feature = "encoding")]
+mod encoding;
+mod error;
+mod forward;
+#[cfg(feature = "flate2")]
+mod gzip;
+mod index;
+pub mod io;
+#[cfg(feature = "std")]
+mod memory;
+#[cfg(feature = "memmap2")]
+mod mmap;
+#[cfg(feature = "serde")]
+mod state;
+
+#[cfg(feature = "std")]
+pub use adapters::ReverseLinesReader;
+pub use adapters::{
+    ReverseLinesBytes, ReverseLinesChunks, ReverseLinesCounted, ReverseLinesEnumerated,
+    ReverseLinesFiltered, ReverseLinesMapped, ReverseLinesNonBlank, ReverseLinesNumbered,
+    ReverseLinesWithOffsets,
+};
+#[cfg(feature = "tokio")]
+pub use async_reader::{AsyncReverseLines, ReverseLinesStream};
+pub use builder::ReverseLinesBuilder;
+pub use error::ReverseLinesError;
+pub use index::LineIndex;
+#[cfg(feature = "serde")]
+pub use state::State;
+
 const DEFAULT_SIZE: usize = 4096;
 
 const LF_BYTE: u8 = b'\n';
 const CR_BYTE: u8 = b'\r';
+const NUL_BYTE: u8 = b'\0';
+
+const LS_CHAR: char = '\u{2028}';
+const PS_CHAR: char = '\u{2029}';
+
+/// Line endings recognised by [`ReverseLines::with_line_ending`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Break on `\n`, stripping a preceding `\r` (the default)
+    #[default]
+    Lf,
+    /// Break on `\r` only, as used by classic Mac OS
+    Cr,
+    /// Break on `\r\n` only; a lone `\n` or `\r` is part of the line
+    CrLf,
+    /// Break on any of `\r`, `\n` or `\r\n`, with `\r\n` counting as one
+    /// line break
+    Any,
+}
+
+impl LineEnding {
+    // The delimiters to break on, and whether to strip a `\r` before `\n`
+    fn delimiters(self) -> (Vec<Vec<u8>>, bool) {
+        match self {
+            LineEnding::Lf => (vec![vec![LF_BYTE]], true),
+            LineEnding::Cr => (vec![vec![CR_BYTE]], false),
+            LineEnding::CrLf => (vec![vec![CR_BYTE, LF_BYTE]], false),
+            LineEnding::Any => (vec![vec![LF_BYTE], vec![CR_BYTE]], true),
+        }
+    }
+}
+
+// How text is laid out in bytes, as far as finding line breaks is concerned
+#[cfg_attr(not(feature = "encoding"), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CodeUnits {
+    Utf8,
+    // Any other encoding compatible with ASCII
+    Ascii,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl CodeUnits {
+    fn size(self) -> usize {
+        match self {
+            CodeUnits::Utf16Le | CodeUnits::Utf16Be => 2,
+            CodeUnits::Utf8 | CodeUnits::Ascii => 1,
+        }
+    }
+
+    // Encode the ASCII bytes of a delimiter as code units
+    fn widen(self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            CodeUnits::Utf16Le => bytes.iter().flat_map(|&byte| [byte, 0]).collect(),
+            CodeUnits::Utf16Be => bytes.iter().flat_map(|&byte| [0, byte]).collect(),
+            CodeUnits::Utf8 | CodeUnits::Ascii => bytes.to_vec(),
+        }
+    }
+
+    // Whether `bytes` is the single ASCII character `byte`
+    fn is(self, bytes: &[u8], byte: u8) -> bool {
+        match self {
+            CodeUnits::Utf16Le => bytes == [byte, 0],
+            CodeUnits::Utf16Be => bytes == [0, byte],
+            CodeUnits::Utf8 | CodeUnits::Ascii => bytes == [byte],
+        }
+    }
+
+    // The byte order mark, for encodings which have one
+    fn bom(self) -> &'static [u8] {
+        match self {
+            CodeUnits::Utf8 => &[0xef, 0xbb, 0xbf],
+            CodeUnits::Utf16Le => &[0xff, 0xfe],
+            CodeUnits::Utf16Be => &[0xfe, 0xff],
+            CodeUnits::Ascii => &[],
+        }
+    }
+
+    // The delimiters for the Unicode line separators, if they can be encoded
+    fn unicode_line_breaks(self) -> Result<Vec<Vec<u8>>> {
+        let encode = |ch: char| {
+            let mut units = [0; 2];
+            let units = ch.encode_utf16(&mut units).iter();
+
+            match self {
+                CodeUnits::Utf8 => ch.to_string().into_bytes(),
+                CodeUnits::Utf16Le => units.flat_map(|unit| unit.to_le_bytes()).collect(),
+                CodeUnits::Utf16Be => units.flat_map(|unit| unit.to_be_bytes()).collect(),
+                CodeUnits::Ascii => unreachable!(),
+            }
+        };
+
+        if self == CodeUnits::Ascii {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "unicode line breaks require a Unicode encoding",
+            ));
+        }
+
+        Ok(vec![encode(LS_CHAR), encode(PS_CHAR)])
+    }
+}
+
+// Lines already yielded when the reader grew, which are skipped over once
+// the lines appended after them have been read. Reading then carries on from
+// `start`, before the line ending `line_end`, and stops again at `end`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Gap {
+    start: u64,
+    end: u64,
+    line_end: Vec<u8>,
+}
 
 /// `ReverseLines` struct
+///
+/// When the reader implements `Clone`, so does `ReverseLines`, giving an
+/// independent cursor that carries on from the same line. This works even
+/// for readers sharing a position, such as `&File`.
+///
+/// `ReverseLines` is `Send` when the reader is, so it can be moved to
+/// another thread along with a `File`, and `Sync` when the reader is.
+#[derive(Clone)]
 pub struct ReverseLines<R: Seek + Read> {
     reader: R,
     reader_pos: u64,
+    start: u64,
+    end: Option<u64>,
+    end_pos: u64,
+    front_pos: u64,
     buf_size: u64,
+    delimiters: Vec<Vec<u8>>,
+    strip_cr: bool,
+    code_units: CodeUnits,
+    #[cfg(feature = "encoding")]
+    encoding: Option<&'static encoding_rs::Encoding>,
+    lossy: bool,
+    keep_ends: bool,
+    trim_start: bool,
+    trim_end: bool,
+    skip_bom: bool,
+    fuse_on_error: bool,
+    max_line_len: Option<usize>,
+    line_end: Vec<u8>,
+    gaps: Vec<Gap>,
+    // The raw bytes of a peeked line are left in `scratch`
+    peeked: Option<Option<String>>,
+    scratch: Vec<u8>,
+    buf: Vec<u8>,
+    buf_pos: u64,
     is_error: bool,
 }
 
@@ -57,203 +264,4208 @@ impl<R: Seek + Read> ReverseLines<R> {
     /// Create a new `ReverseLines` struct from a `<R>`. Internal
     /// buffering for iteration will default to 4096 bytes at a time.
     pub fn new(reader: R) -> Result<ReverseLines<R>> {
-        ReverseLines::with_capacity(DEFAULT_SIZE, reader)
+        ReverseLinesBuilder::new().build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>` which replaces
+    /// invalid UTF-8 sequences with `U+FFFD REPLACEMENT CHARACTER` instead
+    /// of yielding an error. Internal buffering for iteration will default
+    /// to 4096 bytes at a time.
+    pub fn new_lossy(reader: R) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new().lossy(true).build(reader)
     }
 
     /// Create a new `ReverseLines` struct from a `<R>`. Interal
     /// buffering for iteration will use `cap` bytes at a time.
-    pub fn with_capacity(cap: usize, mut reader: R) -> Result<ReverseLines<R>> {
-        // Seek to end of reader now
-        let reader_size = reader.seek(SeekFrom::End(0))?;
-
-        let mut reverse_lines = ReverseLines {
-            reader,
-            reader_pos: reader_size,
-            buf_size: cap as u64,
-            is_error: false,
-        };
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `cap` is zero.
+    pub fn with_capacity(cap: usize, reader: R) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new().capacity(cap).build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, splitting lines on
+    /// `delimiter` instead of `\n`. Internal buffering for iteration will
+    /// default to 4096 bytes at a time.
+    ///
+    /// A `\r` preceding the delimiter is only stripped when the delimiter
+    /// is `\n`.
+    pub fn with_delimiter(delimiter: u8, reader: R) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new()
+            .delimiter(delimiter)
+            .build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, splitting lines on
+    /// NUL bytes, as produced by `find -print0` and similar tools. Internal
+    /// buffering for iteration will default to 4096 bytes at a time.
+    ///
+    /// Entries containing invalid UTF-8 will still yield an error; use
+    /// [`ReverseLines::bytes`] to get at the raw bytes instead.
+    pub fn null_separated(reader: R) -> Result<ReverseLines<R>> {
+        ReverseLines::with_delimiter(NUL_BYTE, reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, splitting lines on
+    /// `delimiter` instead of `\n`. Internal buffering for iteration will
+    /// use `cap` bytes at a time.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `cap` is zero.
+    pub fn with_capacity_and_delimiter(
+        cap: usize,
+        delimiter: u8,
+        reader: R,
+    ) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new()
+            .capacity(cap)
+            .delimiter(delimiter)
+            .build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, splitting lines on
+    /// the byte sequence `delimiter`. Internal buffering for iteration will
+    /// default to 4096 bytes at a time.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `delimiter` is
+    /// empty.
+    pub fn with_byte_delimiter(delimiter: &[u8], reader: R) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new()
+            .byte_delimiter(delimiter)
+            .build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, splitting lines on
+    /// the byte sequence `delimiter`. Internal buffering for iteration will
+    /// use `cap` bytes at a time.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `cap` is zero or
+    /// `delimiter` is empty.
+    pub fn with_capacity_and_byte_delimiter(
+        cap: usize,
+        delimiter: &[u8],
+        reader: R,
+    ) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new()
+            .capacity(cap)
+            .byte_delimiter(delimiter)
+            .build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, splitting lines
+    /// according to `line_ending`. Internal buffering for iteration will
+    /// default to 4096 bytes at a time.
+    pub fn with_line_ending(line_ending: LineEnding, reader: R) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new()
+            .line_ending(line_ending)
+            .build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, splitting lines
+    /// according to `line_ending`. Internal buffering for iteration will
+    /// use `cap` bytes at a time.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `cap` is zero.
+    pub fn with_capacity_and_line_ending(
+        cap: usize,
+        line_ending: LineEnding,
+        reader: R,
+    ) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new()
+            .capacity(cap)
+            .line_ending(line_ending)
+            .build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, reading only the
+    /// lines within the byte range from `start` up to (but excluding) `end`.
+    /// Internal buffering for iteration will default to 4096 bytes at a time.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `start` is after
+    /// `end`, or `end` is past the end of the reader.
+    pub fn with_range(start: u64, end: u64, reader: R) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new().range(start, end).build(reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, starting from byte
+    /// `offset` instead of the end of the reader, so only the lines before
+    /// it are read. Internal buffering for iteration will default to 4096
+    /// bytes at a time.
+    ///
+    /// Passing the [`position`](ReverseLines::position) of an earlier
+    /// `ReverseLines` resumes where it stopped. With CRLF line endings, this
+    /// may fall between the CR and the LF, and the CR is then taken to be
+    /// part of the line ending as before.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `offset` is past
+    /// the end of the reader.
+    pub fn with_start_offset(offset: u64, reader: R) -> Result<ReverseLines<R>> {
+        ReverseLines::with_range(0, offset, reader)
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>`, reading only the
+    /// last `n` bytes of it, so that however long the lines are, nothing
+    /// before them is read. The line at the start of them may have been cut
+    /// off, which can be prevented with
+    /// [`ReverseLinesBuilder::skip_partial_line`]. Internal buffering for
+    /// iteration will default to 4096 bytes at a time.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let reverse_lines = ReverseLines::with_tail_bytes(11, file).unwrap();
+    ///
+    ///  let lines = reverse_lines.collect::<Result<Vec<_>, _>>().unwrap();
+    ///  assert_eq!(lines, ["UVWXYZ", "RST"]);
+    /// ```
+    pub fn with_tail_bytes(n: u64, reader: R) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new().tail_bytes(n).build(reader)
+    }
+
+    /// Set whether invalid UTF-8 sequences are replaced with
+    /// `U+FFFD REPLACEMENT CHARACTER` instead of yielding an error. By
+    /// default an error is yielded.
+    pub fn lossy(mut self, lossy: bool) -> ReverseLines<R> {
+        self.lossy = lossy;
+        self
+    }
+
+    /// Set whether each yielded line retains its terminator (`\r\n`, the
+    /// delimiter, or nothing for a final line without one). By default the
+    /// terminator is stripped.
+    pub fn keep_ends(mut self, keep_ends: bool) -> ReverseLines<R> {
+        self.keep_ends = keep_ends;
+        self
+    }
+
+    /// Set whether ASCII whitespace is trimmed from both ends of each yielded
+    /// line, as with [`ReverseLines::trim_start`] and
+    /// [`ReverseLines::trim_end`] together. By default it is not.
+    pub fn trim(self, trim: bool) -> ReverseLines<R> {
+        self.trim_start(trim).trim_end(trim)
+    }
+
+    /// Set whether ASCII whitespace is trimmed from the start of each yielded
+    /// line, after any byte order mark. By default it is not.
+    pub fn trim_start(mut self, trim_start: bool) -> ReverseLines<R> {
+        self.trim_start = trim_start;
+        self
+    }
+
+    /// Set whether ASCII whitespace is trimmed from the end of each yielded
+    /// line, before its terminator if that is kept. By default it is not.
+    pub fn trim_end(mut self, trim_end: bool) -> ReverseLines<R> {
+        self.trim_end = trim_end;
+        self
+    }
+
+    /// Set whether iteration stops after an error from the reader, or a line
+    /// longer than the maximum line length. By default it does, and `next`
+    /// returns `None` from then on.
+    ///
+    /// Otherwise, the error is yielded and the position is left unchanged,
+    /// so that the next call tries to read the same line again. This allows
+    /// retrying after a transient error, but an error which persists is
+    /// yielded again on every call, so loops over the lines must stop on
+    /// errors themselves.
+    pub fn fuse_on_error(mut self, fuse_on_error: bool) -> ReverseLines<R> {
+        self.fuse_on_error = fuse_on_error;
+        self
+    }
+
+    /// Set whether a byte order mark at the start of the reader is stripped
+    /// from the first line, which is the last line yielded. By default it is
+    /// kept.
+    pub fn skip_bom(mut self, skip_bom: bool) -> ReverseLines<R> {
+        self.skip_bom = skip_bom;
+        self
+    }
+
+    /// Set the maximum length in bytes of a line, excluding its terminator.
+    /// A longer line yields an error of kind `ErrorKind::InvalidData` instead
+    /// of being buffered, after which iteration stops. By default there is
+    /// no limit.
+    ///
+    /// As with any error, the position is left at the end of the line, so
+    /// at most about `limit` bytes of it are read.
+    pub fn max_line_len(mut self, limit: usize) -> ReverseLines<R> {
+        self.max_line_len = Some(limit);
+        self
+    }
+
+    /// Change how many bytes are read at a time for the lines still to come,
+    /// such as to use larger reads once it is clear that the whole reader
+    /// will be read.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidInput` if `cap` is zero.
+    pub fn set_capacity(&mut self, cap: usize) -> Result<()> {
+        if cap == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "capacity must not be zero",
+            ));
+        }
+
+        self.buf_size = cap as u64;
+
+        Ok(())
+    }
+
+    /// Restart iteration from the end of the reader, or of the range being
+    /// read, as if newly created. This also allows iteration to continue
+    /// after an I/O error, and discards any peeked line.
+    ///
+    /// Without a range, the end is found again, so anything appended to the
+    /// reader since is read too.
+    pub fn rewind(&mut self) -> Result<()> {
+        self.is_error = false;
+        self.peeked = None;
+        self.front_pos = self.start;
+        self.gaps.clear();
+
+        self.seek_to_end()?;
+        self.skip_trailing_terminator()
+    }
+
+    /// Restart iteration from the end, as with [`ReverseLines::rewind`], and
+    /// skip lines so that `next` yields `line` next, counting from 0 at the
+    /// last line. The lines skipped are scanned but not decoded.
+    ///
+    /// Unlike [`ReverseLines::seek_to_line`], which counts from the top,
+    /// this needs no index, but scans every line after `line` each time.
+    /// Returns an error of kind `ErrorKind::InvalidInput` if there is no such
+    /// line, leaving no lines to be yielded.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///
+    ///  reverse_lines.seek_to_line_from_end(2).unwrap();
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "GHIJK");
+    /// ```
+    pub fn seek_to_line_from_end(&mut self, line: usize) -> Result<()> {
+        self.rewind()?;
+
+        if self.skip_lines(line)? < line || self.at_start() {
+            return Err(Error::new(ErrorKind::InvalidInput, "line out of range"));
+        }
+
+        Ok(())
+    }
+
+    /// Check the length of the reader again, for readers such as log files
+    /// which may be appended to during iteration, and return the number of
+    /// bytes appended since the end was found, which is zero if the reader
+    /// has not grown. Iteration is left as it was, so the lines appended are
+    /// not read; they can be read forward from the old end, or included with
+    /// [`ReverseLines::extend_to_end`].
+    ///
+    /// The end is found when iteration starts or restarts, such as by
+    /// [`ReverseLines::rewind`], so this keeps counting from there until
+    /// then. A range with an end never grows. If the reader has been
+    /// truncated to before the current position, the rest of its lines can
+    /// no longer be read and an error of kind `InvalidData` is returned.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::io::Cursor;
+    ///
+    ///  let log = Cursor::new(b"ABCDEF\nGHIJK\n".to_vec());
+    ///  let mut reverse_lines = ReverseLines::new(log).unwrap();
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "GHIJK");
+    ///
+    ///  let log = reverse_lines.get_mut().get_mut();
+    ///  log.extend_from_slice(b"LMNOPQRST\nUVWXYZ\n");
+    ///  assert_eq!(reverse_lines.refresh_end().unwrap(), 17);
+    ///
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "ABCDEF");
+    ///  assert!(reverse_lines.next().is_none());
+    /// ```
+    pub fn refresh_end(&mut self) -> Result<u64> {
+        let len = self.reader.seek(SeekFrom::End(0))?;
+        if len < self.reader_pos {
+            return Err(truncated());
+        }
+
+        if self.end.is_some() {
+            return Ok(0);
+        }
+
+        Ok(len.saturating_sub(self.end_pos))
+    }
+
+    /// Check the length of the reader again, as with
+    /// [`ReverseLines::refresh_end`], and if it has grown, yield the lines
+    /// appended since next, then carry on with the lines not yet read from
+    /// before. The number of bytes appended is returned, which is zero if the
+    /// reader has not grown.
+    ///
+    /// Once the reader has grown, [`position`](ReverseLines::position) moves
+    /// to the new end, and goes back to where it was once the lines appended
+    /// have been read. Lines already yielded are not yielded again, other
+    /// than by [`ReverseLines::rewind`], which starts over from the new end.
+    /// A peeked line is still yielded first.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::io::Cursor;
+    ///
+    ///  let log = Cursor::new(b"ABCDEF\nGHIJK\n".to_vec());
+    ///  let mut reverse_lines = ReverseLines::new(log).unwrap();
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "GHIJK");
+    ///
+    ///  let log = reverse_lines.get_mut().get_mut();
+    ///  log.extend_from_slice(b"LMNOPQRST\nUVWXYZ\n");
+    ///  assert_eq!(reverse_lines.extend_to_end().unwrap(), 17);
+    ///
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "UVWXYZ");
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "LMNOPQRST");
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "ABCDEF");
+    ///  assert!(reverse_lines.next().is_none());
+    /// ```
+    pub fn extend_to_end(&mut self) -> Result<u64> {
+        let len = self.reader.seek(SeekFrom::End(0))?;
+        if len < self.reader_pos {
+            return Err(truncated());
+        }
+
+        // After shrinking, anything appended starts from the new end
+        self.end_pos = min(self.end_pos, len);
+        if self.end.is_some() || len == self.end_pos {
+            return Ok(0);
+        }
+
+        // The lines appended are read first, then the lines not yet read
+        // from before
+        let appended = len - self.end_pos;
+        self.gaps.push(Gap {
+            start: self.reader_pos,
+            end: self.end_pos,
+            line_end: mem::take(&mut self.line_end),
+        });
+        self.reader_pos = len;
+        self.end_pos = len;
+        self.skip_trailing_terminator()?;
+
+        Ok(appended)
+    }
+
+    /// Return the current byte offset into the reader. Everything before
+    /// this offset (and after the start of the range, if one was given, and
+    /// any lines yielded by `next_back`) has yet to be read, and every line
+    /// already yielded by `next` lies after it, other than those before the
+    /// old end once [`ReverseLines::extend_to_end`] finds the reader has
+    /// grown.
+    pub fn position(&self) -> u64 {
+        self.reader_pos
+    }
+
+    /// Return whether iteration stopped because of an I/O error, rather than
+    /// because every line was read. This stays `true` until the next call to
+    /// [`ReverseLines::rewind`] or another method restarting iteration.
+    ///
+    /// Lines which fail to decode do not stop iteration, so do not count.
+    pub fn errored(&self) -> bool {
+        self.is_error
+    }
+
+    /// Return whether every line has been read, up to the start of the reader
+    /// or of the range being read, or up to the lines already read by
+    /// `next_back`.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///
+    ///  while let Some(line) = reverse_lines.next() {
+    ///      line.unwrap();
+    ///  }
+    ///  assert!(reverse_lines.at_start());
+    ///  assert!(!reverse_lines.errored());
+    /// ```
+    pub fn at_start(&self) -> bool {
+        !matches!(self.peeked, Some(Some(_)))
+            && self.gaps.is_empty()
+            && self.reader_pos == self.front_pos
+            && self.line_end.is_empty()
+    }
+
+    /// Read the next line into `buf`, reusing its allocation. The contents
+    /// of `buf` are replaced, and the length of the line in bytes is
+    /// returned, or `None` once there are no lines left.
+    ///
+    /// Lines are handled exactly as by `next`, including yielding
+    /// [`ReverseLinesError::Utf8`] for invalid UTF-8.
+    pub fn read_line_into(
+        &mut self,
+        buf: &mut String,
+    ) -> result::Result<Option<usize>, ReverseLinesError> {
+        if let Some(peeked) = self.peeked.take() {
+            return Ok(peeked.map(|line| {
+                *buf = line;
+                buf.len()
+            }));
+        }
+
+        let mut result = mem::take(buf).into_bytes();
+        result.clear();
+
+        match self.next_bytes_into(&mut result, false) {
+            Some(Ok(())) => {
+                *buf = self.decode(result)?;
+                Ok(Some(buf.len()))
+            }
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Skip the next `n` lines without decoding them or keeping their
+    /// contents in memory. Skipping stops early if there are fewer than `n`
+    /// lines left, so the number of lines actually skipped is returned.
+    pub fn skip_lines(&mut self, n: usize) -> Result<usize> {
+        let mut result = Vec::new();
+        let mut first = 0;
+
+        if n > 0 {
+            match self.peeked.take() {
+                Some(Some(_)) => first = 1,
+                Some(None) => return Ok(0),
+                None => (),
+            }
+        }
+
+        for skipped in first..n {
+            match self.next_bytes_into(&mut result, true) {
+                Some(line) => line?,
+                None => return Ok(skipped),
+            }
+
+            result.clear();
+        }
+
+        Ok(n)
+    }
+
+    /// Decode the next line and return it without consuming it, so that the
+    /// following call to `next` yields it again. The position moves past the
+    /// line as soon as it is peeked.
+    ///
+    /// Errors are not kept, but returned straight away and not by `next`.
+    /// After an I/O error, iteration stops as usual.
+    ///
+    /// A peeked line is seen by `next`, [`ReverseLines::read_line_into`],
+    /// [`ReverseLines::skip_lines`], [`ReverseLines::bytes`] and
+    /// [`ReverseLines::into_reader`], but not by other methods.
+    pub fn peek_line(&mut self) -> Option<result::Result<&str, ReverseLinesError>> {
+        if self.peeked.is_none() {
+            match self.next() {
+                Some(Ok(line)) => self.peeked = Some(Some(line)),
+                Some(Err(e)) => return Some(Err(e)),
+                None => self.peeked = Some(None),
+            }
+        }
 
-        // Handle any trailing new line characters for the reader
-        // so the first next call does not return Some("")
+        self.peeked.as_ref()?.as_deref().map(Ok)
+    }
+
+    /// Read all of the remaining lines, last line first, stopping at the
+    /// first error. This is the same as collecting into a `Result`, but also
+    /// stops when iteration is not fused on errors.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let reverse_lines = ReverseLines::new(file).unwrap();
+    ///
+    ///  let lines = reverse_lines.collect_lines().unwrap();
+    ///  assert_eq!(lines.first().unwrap(), "UVWXYZ");
+    ///  assert_eq!(lines.last().unwrap(), "ABCDEF");
+    /// ```
+    pub fn collect_lines(mut self) -> result::Result<Vec<String>, ReverseLinesError> {
+        let mut lines = Vec::new();
+        for line in &mut self {
+            lines.push(line?);
+        }
 
-        // Read at most 2 bytes
-        let end_size = min(reader_size, 2);
-        let end_buf = reverse_lines.read_to_buffer(end_size)?;
+        Ok(lines)
+    }
 
-        if end_size == 1 {
-            if end_buf[0] != LF_BYTE {
-                reverse_lines.move_reader_position(1)?;
+    /// Find the last line satisfying `predicate`, that is, the first one
+    /// yielded, and stop there so that nothing before it is read. Lines which
+    /// do not match are not kept, and the first error encountered is
+    /// returned instead.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///
+    ///  let line = reverse_lines.find_last(|line| line.starts_with('G')).unwrap();
+    ///  assert_eq!(line.unwrap(), "GHIJK");
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "ABCDEF");
+    /// ```
+    pub fn find_last<F: FnMut(&str) -> bool>(
+        &mut self,
+        mut predicate: F,
+    ) -> result::Result<Option<String>, ReverseLinesError> {
+        while let Some(line) = self.next_cow() {
+            let line = line?;
+            if predicate(&line) {
+                return Ok(Some(line.into_owned()));
             }
-        } else if end_size == 2 {
-            if end_buf[0] != CR_BYTE {
-                reverse_lines.move_reader_position(1)?;
+        }
+
+        Ok(None)
+    }
+
+    /// Count the lines left to be yielded, without decoding them or keeping
+    /// their contents in memory. The position is left where it was, so this
+    /// is the number of lines `next` would yield from here.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///
+    ///  assert_eq!(reverse_lines.count_lines().unwrap(), 4);
+    ///  assert_eq!(reverse_lines.count(), 4);
+    /// ```
+    pub fn count_lines(&mut self) -> Result<usize> {
+        let reader_pos = self.reader_pos;
+        let line_end = self.line_end.clone();
+        let gaps = self.gaps.clone();
+        let is_error = self.is_error;
+
+        let mut count = match self.peeked {
+            Some(Some(_)) => 1,
+            _ => 0,
+        };
+        let mut result = Vec::new();
+        let counted = loop {
+            match self.next_bytes_into(&mut result, true) {
+                Some(Ok(())) => count += 1,
+                Some(Err(e)) => break Err(e),
+                None => break Ok(count),
             }
 
-            if end_buf[1] != LF_BYTE {
-                reverse_lines.move_reader_position(1)?;
+            result.clear();
+        };
+
+        // Go back to where we were before counting
+        self.reader_pos = reader_pos;
+        self.line_end = line_end;
+        self.gaps = gaps;
+        self.is_error = is_error;
+
+        counted
+    }
+
+    /// Find the offset of the start of every line in the reader, or in the
+    /// range being read, in ascending order. The lines are scanned from the
+    /// end as usual, but none of them are decoded or kept in memory.
+    ///
+    /// Iteration restarts from the end both before and after the scan, so
+    /// lines already read are included, and any peeked line is discarded.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///
+    ///  assert_eq!(reverse_lines.line_offsets().unwrap(), vec![0, 7, 13, 23]);
+    /// ```
+    pub fn line_offsets(&mut self) -> Result<Vec<u64>> {
+        self.rewind()?;
+
+        let mut offsets = Vec::new();
+        let mut result = Vec::new();
+        while let Some(line) = self.next_bytes_into(&mut result, true) {
+            line?;
+            offsets.push(self.line_start());
+            result.clear();
+        }
+
+        offsets.reverse();
+        self.rewind()?;
+
+        Ok(offsets)
+    }
+
+    /// Read the next line without copying it where possible, borrowing it
+    /// from a buffer kept for reading lines. This is the same as `next`,
+    /// except that only lines which had to be changed to decode them, such as
+    /// by replacing invalid UTF-8 or converting from another encoding, are
+    /// allocated.
+    ///
+    /// As the line borrows the `ReverseLines`, this cannot be done by an
+    /// `Iterator`, so lines are read with `while let` instead.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///
+    ///  let mut count = 0;
+    ///  while let Some(line) = reverse_lines.next_cow() {
+    ///      if line.unwrap().contains('Z') {
+    ///          count += 1;
+    ///      }
+    ///  }
+    ///  assert_eq!(count, 1);
+    /// ```
+    pub fn next_cow(&mut self) -> Option<result::Result<Cow<'_, str>, ReverseLinesError>> {
+        if let Some(peeked) = self.peeked.take() {
+            return peeked.map(|line| Ok(Cow::Owned(line)));
+        }
+
+        let mut result = mem::take(&mut self.scratch);
+        result.clear();
+
+        let line = self.next_bytes_into(&mut result, false);
+        self.scratch = result;
+
+        match line? {
+            Ok(()) => Some(self.decode_slice(&self.scratch)),
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+
+    /// Get a reference to the wrapped reader, such as to query the metadata
+    /// of a `File` mid-iteration. The reader is not necessarily positioned at
+    /// [`ReverseLines::position`], as it is only moved there when it is
+    /// handed out by [`ReverseLines::get_mut`] or
+    /// [`ReverseLines::into_inner`].
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///
+    ///  reverse_lines.next();
+    ///  assert_eq!(reverse_lines.get_ref().metadata().unwrap().len(), 30);
+    /// ```
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Get a mutable reference to the wrapped reader. This is intended for
+    /// inspection only.
+    ///
+    /// The reader is first moved to [`ReverseLines::position`], unless
+    /// seeking there fails. Lines are always read from absolute offsets, so
+    /// moving the reader does not affect iteration, but changing what it
+    /// reads before the position will.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.sync_reader();
+        &mut self.reader
+    }
+
+    /// Consume this `ReverseLines` and return the wrapped reader. The reader
+    /// is left positioned at [`ReverseLines::position`], unless seeking
+    /// there fails.
+    pub fn into_inner(mut self) -> R {
+        self.sync_reader();
+        self.reader
+    }
+
+    // Move the reader to the position before handing it out. Lines are
+    // mostly read from the buffer, so the reader is not kept there, and the
+    // callers have no way to return an error
+    fn sync_reader(&mut self) {
+        let _ = self.reader.seek(SeekFrom::Start(self.reader_pos));
+    }
+
+    // Seek to the end of the reader, or of the range being read
+    fn seek_to_end(&mut self) -> Result<()> {
+        self.buf.clear();
+        self.reader_pos = match self.end {
+            Some(end) => self.reader.seek(SeekFrom::Start(end))?,
+            None => self.reader.seek(SeekFrom::End(0))?,
+        };
+        self.end_pos = self.reader_pos;
+
+        Ok(())
+    }
+
+    // Handle any trailing delimiter for the reader
+    // so the first next call does not return Some("")
+    fn skip_trailing_terminator(&mut self) -> Result<()> {
+        // Read the longest delimiter length, plus a character when a CR may
+        // precede the LF
+        let cr = self.code_units.widen(&[CR_BYTE]);
+        let end_size = min(
+            self.reader_pos - self.region_start(),
+            (self.max_delimiter_size() + if self.strip_cr { cr.len() } else { 0 }) as u64,
+        );
+        let end_buf = self.read_to_buffer(end_size)?;
+
+        let mut strip_size = 0;
+        if let Some(delimiter) = self.delimiters.iter().find(|delimiter| {
+            end_buf.ends_with(delimiter)
+                && self.is_aligned(self.reader_pos + (end_buf.len() - delimiter.len()) as u64)
+        }) {
+            strip_size += delimiter.len();
+
+            if self.strip_cr
+                && self.code_units.is(delimiter, LF_BYTE)
+                && end_buf[..end_buf.len() - delimiter.len()].ends_with(&cr)
+            {
+                strip_size += cr.len();
             }
+        } else if self.strip_cr
+            && end_buf.ends_with(&cr)
+            && self.is_aligned(self.reader_pos + (end_buf.len() - cr.len()) as u64)
+            && self.lf_follows(self.reader_pos + end_size)?
+        {
+            // The end falls within a CRLF line ending, such as when starting
+            // from the position of an earlier `ReverseLines`
+            strip_size += cr.len();
+        }
+
+        let strip_size = strip_size as u64;
+        self.line_end = end_buf[(end_size - strip_size) as usize..].to_vec();
+        self.reader_pos += end_size - strip_size;
+
+        Ok(())
+    }
+
+    // Whether an LF starts at `pos`, past the lines being read
+    fn lf_follows(&mut self, pos: u64) -> Result<bool> {
+        let mut next = self.code_units.widen(&[LF_BYTE]);
+
+        self.reader.seek(SeekFrom::Start(pos))?;
+        match self.reader.read_exact(&mut next) {
+            Ok(()) => Ok(self.code_units.is(&next, LF_BYTE)),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Where reading back stops: after the lines already yielded when the
+    // reader last grew, or otherwise at the front
+    fn region_start(&self) -> u64 {
+        self.gaps.last().map_or(self.front_pos, |gap| gap.end)
+    }
+
+    // The offset of the first byte of the most recently yielded line
+    fn line_start(&self) -> u64 {
+        self.reader_pos + self.line_end.len() as u64
+    }
+
+    // Whether `pos` lies on a character boundary, for encodings where
+    // characters are wider than a byte
+    fn is_aligned(&self, pos: u64) -> bool {
+        (pos - self.start).is_multiple_of(self.code_units.size() as u64)
+    }
+
+    fn max_delimiter_size(&self) -> usize {
+        self.delimiters.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    // How many of the most recently read bytes may yet turn out to be part of
+    // a delimiter
+    fn partial_delimiter_size(&self) -> Result<usize> {
+        self.max_delimiter_size()
+            .checked_sub(1)
+            .ok_or_else(no_delimiters)
+    }
+
+    // Whether a line read by `next_bytes_into` is empty but for any line
+    // ending kept with it
+    fn is_blank(&self, line: &[u8]) -> bool {
+        if line.is_empty() || !self.keep_ends {
+            return line.is_empty();
         }
 
-        Ok(reverse_lines)
+        self.delimiters.iter().any(|delimiter| delimiter == line)
+            || (self.strip_cr && self.code_units.widen(b"\r\n") == line)
     }
 
+    // Read up to `size` bytes before the position, moving the position back
+    // over them. Bytes left over in the buffer from the last read are
+    // returned without reading them again, leaving the reader where it was;
+    // the buffer should be handed back by setting `self.buf` once done with.
     fn read_to_buffer(&mut self, size: u64) -> Result<Vec<u8>> {
-        let mut buf = vec![0; size as usize];
-        let offset = -(size as i64);
+        let mut buf = mem::take(&mut self.buf);
+
+        if self.buf_pos < self.reader_pos && self.reader_pos <= self.buf_pos + buf.len() as u64 {
+            buf.truncate((self.reader_pos - self.buf_pos) as usize);
 
-        self.reader.seek(SeekFrom::Current(offset))?;
-        self.reader.read_exact(&mut buf[0..(size as usize)])?;
-        self.reader.seek(SeekFrom::Current(offset))?;
+            // Lines before the front may have been read by `next_back` since
+            if self.buf_pos < self.front_pos {
+                buf.drain(..(self.front_pos - self.buf_pos) as usize);
+                self.buf_pos = self.front_pos;
+            }
+
+            self.reader_pos = self.buf_pos;
+
+            return Ok(buf);
+        }
 
+        buf.clear();
+        buf.resize(size as usize, 0);
+
+        // Seek from the start rather than relative to the reader's position,
+        // which may have been moved by a clone sharing it. The reader is left
+        // after the bytes read, and only returned to the position when it is
+        // handed out
         self.reader_pos -= size;
+        self.buf_pos = self.reader_pos;
+
+        self.reader.seek(SeekFrom::Start(self.reader_pos))?;
+        self.reader
+            .read_exact(&mut buf[0..(size as usize)])
+            .map_err(|e| match e.kind() {
+                // Everything before the position was there to begin with
+                ErrorKind::UnexpectedEof => truncated(),
+                _ => e,
+            })?;
 
         Ok(buf)
     }
 
-    fn move_reader_position(&mut self, offset: u64) -> Result<()> {
-        self.reader.seek(SeekFrom::Current(offset as i64))?;
-        self.reader_pos += offset;
+    // Take the raw bytes of any peeked line, as `peeked.take()` would
+    fn take_peeked_bytes(&mut self) -> Option<Option<Vec<u8>>> {
+        let peeked = self.peeked.take()?;
 
-        Ok(())
+        Some(peeked.map(|_| mem::take(&mut self.scratch)))
     }
-}
 
-impl<R: Read + Seek> Iterator for ReverseLines<R> {
-    type Item = Result<String>;
+    fn next_bytes(&mut self) -> Option<Result<Vec<u8>>> {
+        let mut result = Vec::new();
 
-    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_bytes_into(&mut result, false)?.map(|_| result))
+    }
+
+    // Read the next line into `result`, which must be empty. If `discard` is
+    // set, only as much of the line as is needed to find its start is kept.
+    fn next_bytes_into(&mut self, result: &mut Vec<u8>, discard: bool) -> Option<Result<()>> {
         if self.is_error {
             return None;
         }
 
-        let mut result: Vec<u8> = Vec::new();
+        // Once the lines appended when the reader grew have all been read,
+        // carry on with the older lines before those already yielded
+        if self.line_end.is_empty()
+            && self
+                .gaps
+                .last()
+                .is_some_and(|gap| gap.end == self.reader_pos)
+        {
+            let gap = self.gaps.pop()?;
+            self.reader_pos = gap.start;
+            self.line_end = gap.line_end;
+        }
+
+        // An error leaves the position at the end of the line, so that
+        // without fusing the line can be read again. Reading a line only
+        // ever adds a stripped CR to the start of the line ending
+        let reader_pos = self.reader_pos;
+        let line_end_len = self.line_end.len();
+
+        let line = self.read_line_bytes(result, discard);
+        if let Some(Err(_)) = line {
+            self.reader_pos = reader_pos;
+            self.line_end.drain(..self.line_end.len() - line_end_len);
+            self.is_error = self.fuse_on_error;
+            result.clear();
+        }
+
+        line
+    }
+
+    fn read_line_bytes(&mut self, result: &mut Vec<u8>, discard: bool) -> Option<Result<()>> {
+        let mut line_end: Vec<u8> = Vec::new();
+        let mut strip_cr = self.strip_cr && self.code_units.is(&self.line_end, LF_BYTE);
+        let cr = self.code_units.widen(&[CR_BYTE]);
+
+        // The most recently read bytes may yet turn out to be part of a
+        // delimiter, so allow for them when enforcing the line length limit
+        // or discarding the line
+        let partial_size = match self.partial_delimiter_size() {
+            Ok(partial_size) => partial_size,
+            Err(e) => return Some(Err(e)),
+        };
+        let max_result_size = if discard {
+            Some(max(partial_size, 1))
+        } else {
+            self.max_line_len
+                .map(|limit| limit.saturating_add(partial_size))
+        };
 
+        let mut first_bytes: Vec<u8> = self
+            .delimiters
+            .iter()
+            .map(|delimiter| delimiter[0])
+            .collect();
+        first_bytes.sort_unstable();
+        first_bytes.dedup();
+
+        let region_start = self.region_start();
         'outer: loop {
-            if self.reader_pos < 1 {
-                if !result.is_empty() {
-                    break;
+            if self.reader_pos <= region_start {
+                // The first line may be empty, but still ends with a line
+                // ending, unless it has already been yielded
+                if result.is_empty() && self.line_end.is_empty() {
+                    return None;
                 }
 
-                return None;
+                break;
             }
 
             // Read the of minimum between the desired
             // buffer size or remaining length of the reader
-            let size = min(self.buf_size, self.reader_pos);
+            let size = min(self.buf_size, self.reader_pos - region_start);
 
             match self.read_to_buffer(size) {
                 Ok(buf) => {
-                    for (idx, ch) in buf.iter().enumerate().rev() {
-                        // Found a new line character to break on
-                        if *ch == LF_BYTE {
-                            let mut offset = idx as u64;
-
-                            // Add an extra byte cause of CR character
-                            if idx > 1 && buf[idx - 1] == CR_BYTE {
-                                offset -= 1;
-                            }
+                    let mut end = buf.len();
 
-                            match self.reader.seek(SeekFrom::Current(offset as i64)) {
-                                Ok(_) => {
-                                    self.reader_pos += offset;
-                                    break 'outer;
-                                }
+                    while end > 0 {
+                        // Only the first byte of a delimiter can complete it,
+                        // so the bytes after the last such byte are pushed in
+                        // bulk. Bytes which may be a stripped CR are not
+                        let candidate = if strip_cr {
+                            Some(end - 1)
+                        } else {
+                            memrchr_any(&first_bytes, &buf[..end])
+                        };
+                        let bulk_start = candidate.map_or(0, |idx| idx + 1);
+
+                        result.extend(buf[bulk_start..end].iter().rev());
+                        end = bulk_start;
+
+                        if let Some(idx) = candidate {
+                            end = idx;
+                            result.push(buf[idx]);
 
-                                Err(e) => {
-                                    self.is_error = true;
-                                    return Some(Err(e));
+                            // A CR before the LF ending this line is part of
+                            // the line ending. It may not have been in the same
+                            // buffer as the LF, so it's only checked for now
+                            if strip_cr && result.len() == self.code_units.size() {
+                                strip_cr = false;
+
+                                if result.iter().rev().eq(cr.iter()) {
+                                    self.line_end.splice(0..0, result.drain(..).rev());
+                                    continue;
                                 }
                             }
-                        } else {
-                            result.push(*ch);
+
+                            // Found a delimiter to break on. The result is
+                            // stored backwards, so the delimiter appears
+                            // reversed at its tail; this also catches
+                            // delimiters straddling buffers
+                            let pos = self.reader_pos + idx as u64;
+                            let found = self.delimiters.iter().find(|delimiter| {
+                                result.len() >= delimiter.len()
+                                    && result
+                                        .iter()
+                                        .rev()
+                                        .take(delimiter.len())
+                                        .eq(delimiter.iter())
+                                    && self.is_aligned(pos)
+                            });
+
+                            if let Some(delimiter) = found {
+                                result.truncate(result.len() - delimiter.len());
+                                line_end.extend_from_slice(delimiter);
+
+                                // Keep the rest of the buffer for the next
+                                // line
+                                self.reader_pos = pos;
+                                self.buf = buf;
+                                break 'outer;
+                            }
+                        }
+
+                        if let Some(size) = max_result_size.filter(|size| result.len() > *size) {
+                            if discard {
+                                result.drain(..result.len() - size);
+                                continue;
+                            }
+
+                            return Some(Err(line_too_long()));
                         }
                     }
+
+                    self.buf = buf;
                 }
 
                 Err(e) => {
-                    self.is_error = true;
                     return Some(Err(e));
                 }
             }
         }
 
+        if !discard && self.max_line_len.is_some_and(|limit| result.len() > limit) {
+            return Some(Err(line_too_long()));
+        }
+
         // Reverse the results since they were written backwards
         result.reverse();
 
-        // Convert to a String
-        Some(String::from_utf8(result).map_err(|e| Error::new(ErrorKind::InvalidData, e)))
-    }
-}
-
-impl<R: Read + Seek> FusedIterator for ReverseLines<R> {}
+        if line_end.is_empty() && self.reader_pos == 0 {
+            self.strip_bom(result);
+        }
 
-#[cfg(test)]
-mod tests {
-    use std::fs::File;
+        self.trim_whitespace(result);
 
-    use super::*;
+        // The terminator of this line was found by the previous call (or the
+        // constructor), so swap in the one found for the next line
+        let line_end = mem::replace(&mut self.line_end, line_end);
+        if self.keep_ends {
+            result.extend_from_slice(&line_end);
+        }
 
-    #[test]
-    fn it_handles_empty_files() {
-        let file = File::open("tests/empty_file").unwrap();
-        let mut rev_lines = ReverseLines::new(file).unwrap();
+        Some(Ok(()))
+    }
 
-        assert_matches!(rev_lines.next(), None);
+    // Strip a byte order mark from the first line of the reader
+    fn strip_bom(&self, result: &mut Vec<u8>) {
+        let bom = self.code_units.bom();
+        if self.skip_bom && result.starts_with(bom) {
+            result.drain(..bom.len());
+        }
+    }
+
+    // Trim ASCII whitespace from the ends of a line, as configured
+    fn trim_whitespace(&self, result: &mut Vec<u8>) {
+        let size = self.code_units.size();
+        let is_whitespace = |unit: &[u8]| {
+            b" \t\n\x0c\r"
+                .iter()
+                .any(|&byte| self.code_units.is(unit, byte))
+        };
+
+        if self.trim_end {
+            while result.len() >= size && is_whitespace(&result[result.len() - size..]) {
+                result.truncate(result.len() - size);
+            }
+        }
+
+        if self.trim_start {
+            let start = result
+                .chunks_exact(size)
+                .take_while(|unit| is_whitespace(unit))
+                .count()
+                * size;
+            result.drain(..start);
+        }
+    }
+
+    fn decode(&self, result: Vec<u8>) -> result::Result<String, ReverseLinesError> {
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = self.encoding {
+            return self.decode_with(encoding, &result).map(Cow::into_owned);
+        }
+
+        match String::from_utf8(result) {
+            Ok(line) => Ok(line),
+            Err(e) if self.lossy => Ok(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+            Err(e) => Err(ReverseLinesError::Utf8(e)),
+        }
+    }
+
+    // Decode a line, borrowing it where it needs no changes
+    fn decode_slice<'a>(
+        &self,
+        result: &'a [u8],
+    ) -> result::Result<Cow<'a, str>, ReverseLinesError> {
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = self.encoding {
+            return self.decode_with(encoding, result);
+        }
+
+        match core::str::from_utf8(result) {
+            Ok(line) => Ok(Cow::Borrowed(line)),
+            // Only copy the line into a `Vec` when needed for the error
+            Err(_) => self.decode(result.to_vec()).map(Cow::Owned),
+        }
+    }
+}
+
+impl<R: Seek + Read> fmt::Debug for ReverseLines<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The reader is left out so that `R` need not implement `Debug`
+        f.debug_struct("ReverseLines")
+            .field("reader_pos", &self.reader_pos)
+            .field("buf_size", &self.buf_size)
+            .field("is_error", &self.is_error)
+            .finish_non_exhaustive()
+    }
+}
+
+// Find the last occurrence of any of `needles` in `haystack`
+fn memrchr_any(needles: &[u8], haystack: &[u8]) -> Option<usize> {
+    match *needles {
+        [a] => memchr::memrchr(a, haystack),
+        [a, b] => memchr::memrchr2(a, b, haystack),
+        [a, b, c] => memchr::memrchr3(a, b, c, haystack),
+        _ => haystack.iter().rposition(|byte| needles.contains(byte)),
+    }
+}
+
+fn line_too_long() -> Error {
+    Error::new(ErrorKind::InvalidData, "line exceeds maximum length")
+}
+
+fn no_delimiters() -> Error {
+    Error::new(ErrorKind::InvalidInput, "no delimiters to split lines on")
+}
+
+fn truncated() -> Error {
+    Error::new(ErrorKind::InvalidData, "reader was truncated")
+}
+
+impl<R: Read + Seek> Iterator for ReverseLines<R> {
+    type Item = result::Result<String, ReverseLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(peeked) = self.peeked.take() {
+            return peeked.map(Ok);
+        }
+
+        // This copies the line out of the scratch buffer rather than taking
+        // ownership of it as `String::from_utf8` would, so the scratch buffer
+        // keeps its allocation for the next line
+        self.next_cow().map(|line| line.map(Cow::into_owned))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let peeked = match self.peeked {
+            Some(Some(_)) => 1,
+            Some(None) => return (0, Some(0)),
+            None => 0,
+        };
+
+        if self.is_error {
+            return (0, Some(0));
+        }
+
+        // Every remaining line but the last needs at least one byte, either of
+        // content or of its delimiter, and the last is followed by the line
+        // ending found, so the remaining length is an upper bound. The same
+        // goes for the lines on either side of those already yielded when
+        // the reader grew
+        let mut region_start = self.front_pos;
+        let mut regions = Vec::new();
+        for gap in &self.gaps {
+            regions.push((region_start, gap.start, &gap.line_end));
+            region_start = gap.end;
+        }
+        regions.push((region_start, self.reader_pos, &self.line_end));
+        let remaining =
+            regions
+                .into_iter()
+                .try_fold(0usize, |remaining, (start, end, line_end)| {
+                    usize::try_from(end - start)
+                        .ok()?
+                        .checked_add(usize::from(!line_end.is_empty()))?
+                        .checked_add(remaining)
+                });
+        (
+            peeked,
+            remaining.and_then(|remaining| remaining.checked_add(peeked)),
+        )
+    }
+}
+
+/// Lines are yielded from the front by reading forward from the start of the
+/// reader, or of the range being read, until meeting the lines yielded from
+/// the end. So `next_back` yields the first line, then the second, and so on.
+///
+/// ```
+///  use reverse_lines::ReverseLines;
+///  use std::fs::File;
+///
+///  let file = File::open("tests/multi_line_file").unwrap();
+///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+///
+///  assert_eq!(reverse_lines.next_back().unwrap().unwrap(), "ABCDEF");
+///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "UVWXYZ");
+/// ```
+impl<R: Read + Seek> DoubleEndedIterator for ReverseLines<R> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.next_bytes_back()?;
+
+        Some(
+            result
+                .map_err(ReverseLinesError::from)
+                .and_then(|result| self.decode(result)),
+        )
+    }
+}
+
+impl<R: Read + Seek> FusedIterator for ReverseLines<R> {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::cell::Cell;
+    use std::fs::File;
+    use std::io::{BufRead, BufReader, Cursor};
+    use std::rc::Rc;
+
+    use super::*;
+
+    // Reader which records the lowest offset read from
+    struct TrackingReader<R> {
+        inner: R,
+        lowest: Rc<Cell<u64>>,
+    }
+
+    impl<R: Seek> Seek for TrackingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    impl<R: Read + Seek> Read for TrackingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let pos = self.inner.stream_position()?;
+            self.lowest.set(min(self.lowest.get(), pos));
+            self.inner.read(buf)
+        }
+    }
+
+    // Reader which counts the calls made to it
+    struct CountingReader<R> {
+        inner: R,
+        reads: Rc<Cell<usize>>,
+        seeks: Rc<Cell<usize>>,
+    }
+
+    impl<R: Seek> Seek for CountingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            self.seeks.set(self.seeks.get() + 1);
+            self.inner.seek(pos)
+        }
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.reads.set(self.reads.get() + 1);
+            self.inner.read(buf)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl<R: tokio::io::AsyncSeek + Unpin> tokio::io::AsyncSeek for CountingReader<R> {
+        fn start_seek(mut self: core::pin::Pin<&mut Self>, pos: SeekFrom) -> Result<()> {
+            self.seeks.set(self.seeks.get() + 1);
+            core::pin::Pin::new(&mut self.inner).start_seek(pos)
+        }
+
+        fn poll_complete(
+            mut self: core::pin::Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+        ) -> core::task::Poll<Result<u64>> {
+            core::pin::Pin::new(&mut self.inner).poll_complete(cx)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl<R: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for CountingReader<R> {
+        fn poll_read(
+            mut self: core::pin::Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> core::task::Poll<Result<()>> {
+            self.reads.set(self.reads.get() + 1);
+            core::pin::Pin::new(&mut self.inner).poll_read(cx, buf)
+        }
+    }
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    // Checked for any reader when compiling, rather than only for `File`
+    fn assert_thread_safe<R: Read + Seek + Send + Sync>() {
+        assert_send::<ReverseLines<R>>();
+        assert_sync::<ReverseLines<R>>();
+        assert_send::<ReverseLinesBytes<R>>();
+        assert_send::<ReverseLinesChunks<R>>();
+        assert_send::<ReverseLinesEnumerated<R>>();
+        assert_send::<ReverseLinesFiltered<R, fn(&[u8]) -> bool>>();
+        assert_send::<ReverseLinesNumbered<R>>();
+        assert_send::<ReverseLinesWithOffsets<R>>();
     }
 
     #[test]
-    fn it_handles_file_with_one_line() {
-        let file = File::open("tests/one_line_file").unwrap();
-        let mut rev_lines = ReverseLines::new(file).unwrap();
+    fn it_is_send_and_sync() {
+        assert_thread_safe::<File>();
+        assert_send::<ReverseLinesError>();
+        assert_sync::<ReverseLinesError>();
 
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCD".to_string());
-        assert_matches!(rev_lines.next(), None);
+        #[cfg(feature = "tokio")]
+        {
+            assert_send::<AsyncReverseLines<tokio::fs::File>>();
+            assert_send::<ReverseLinesStream<tokio::fs::File>>();
+        }
     }
 
     #[test]
-    fn it_handles_file_with_multi_lines() {
+    fn it_rejects_zero_capacity() {
         let file = File::open("tests/multi_line_file").unwrap();
+        let result = ReverseLines::with_capacity(0, file);
+
+        assert_matches!(result.err(), Some(e) if e.kind() == ErrorKind::InvalidInput);
+
+        let results = [
+            ReverseLines::with_capacity_and_delimiter(0, b'\n', Cursor::new(b"a\nb")),
+            ReverseLines::with_capacity_and_byte_delimiter(0, b"\n", Cursor::new(b"a\nb")),
+            ReverseLines::with_capacity_and_line_ending(0, LineEnding::Any, Cursor::new(b"a\nb")),
+            ReverseLinesBuilder::new()
+                .capacity(0)
+                .build(Cursor::new(b"a\nb")),
+        ];
+
+        for result in results {
+            assert_matches!(result.err(), Some(e) if e.kind() == ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn it_changes_capacity_mid_iteration() {
+        let file = File::open("tests/crlf_file").unwrap();
+        let expected: Vec<String> = ReverseLines::new(file)
+            .unwrap()
+            .map(|line| line.unwrap())
+            .collect();
+
+        for capacity in 1..=8 {
+            let file = File::open("tests/crlf_file").unwrap();
+            let mut rev_lines = ReverseLines::with_capacity(capacity, file).unwrap();
+
+            let mut lines = Vec::new();
+            for next_capacity in [3, 1, 64, 2, 5] {
+                if let Some(line) = rev_lines.next() {
+                    lines.push(line.unwrap());
+                }
+                rev_lines.set_capacity(next_capacity).unwrap();
+            }
+
+            assert_matches!(rev_lines.next(), None);
+            assert_eq!(lines, expected);
+        }
+
+        let file = File::open("tests/crlf_file").unwrap();
         let mut rev_lines = ReverseLines::new(file).unwrap();
+        assert_matches!(rev_lines.set_capacity(0), Err(e) if e.kind() == ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn it_rewinds() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, file).unwrap();
 
         assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCDEF".to_string());
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "LMNOPQRST");
+
+        rev_lines.rewind().unwrap();
+        assert_eq!(rev_lines.position(), 29);
+
+        let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["UVWXYZ", "LMNOPQRST", "GHIJK", "ABCDEF"]);
+
+        rev_lines.rewind().unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        // Rewinding lets iteration continue after an I/O error has stopped it
+        let cursor = Cursor::new(b"abcdefghijkl\nb\n");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().max_line_len(4);
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_matches!(rev_lines.next(), Some(Err(_)));
         assert_matches!(rev_lines.next(), None);
+
+        rev_lines.rewind().unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
     }
 
     #[test]
-    fn it_handles_file_with_blank_lines() {
-        let file = File::open("tests/blank_line_file").unwrap();
-        let mut rev_lines = ReverseLines::new(file).unwrap();
+    fn it_rewinds_after_appending() {
+        let cursor = Cursor::new(b"a\nb\n".to_vec());
+        let mut rev_lines = ReverseLines::with_capacity(3, cursor).unwrap();
 
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "XYZ".to_string());
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCD".to_string());
+        let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["b", "a"]);
         assert_matches!(rev_lines.next(), None);
+
+        rev_lines.rewind().unwrap();
+        let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["b", "a"]);
+
+        rev_lines.get_mut().get_mut().extend_from_slice(b"c\r\nd\n");
+        rev_lines.rewind().unwrap();
+        let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["d", "c", "b", "a"]);
+    }
+
+    // Reader of a synthetic stream larger than `i64::MAX` bytes, made of
+    // lines of "xyz"
+    struct HugeReader {
+        pos: u64,
+    }
+
+    const HUGE_SIZE: u64 = i64::MAX as u64 + 17;
+
+    impl Seek for HugeReader {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            let pos = match pos {
+                SeekFrom::Start(offset) => Some(offset),
+                SeekFrom::End(offset) => HUGE_SIZE.checked_add_signed(offset),
+                SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+            };
+
+            self.pos = pos.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "bad seek"))?;
+            Ok(self.pos)
+        }
+    }
+
+    impl Read for HugeReader {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let len = min(buf.len() as u64, HUGE_SIZE.saturating_sub(self.pos)) as usize;
+            for (byte, pos) in buf[..len].iter_mut().zip(self.pos..) {
+                *byte = b"xyz\n"[(pos % 4) as usize];
+            }
+
+            self.pos += len as u64;
+            Ok(len)
+        }
     }
 
     #[test]
-    fn it_handles_file_with_multi_lines_and_with_capacity() {
-        let file = File::open("tests/multi_line_file").unwrap();
-        let mut rev_lines = ReverseLines::with_capacity(5, file).unwrap();
+    fn it_handles_readers_larger_than_i64_max() {
+        let reader = HugeReader { pos: 0 };
+        let mut rev_lines = ReverseLines::with_capacity(3, reader).unwrap();
+
+        assert_eq!(rev_lines.position(), HUGE_SIZE - 1);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "xyz".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "xyz".to_string());
+        assert_eq!(rev_lines.position(), HUGE_SIZE - 9);
+
+        // Every way of moving around uses absolute offsets
+        assert_eq!(
+            rev_lines.next_forward().unwrap().unwrap(),
+            "xyz".to_string()
+        );
+        assert_matches!(rev_lines.refresh_end(), Ok(0));
+        rev_lines.rewind().unwrap();
+        assert_eq!(rev_lines.position(), HUGE_SIZE - 1);
+
+        let reader = HugeReader { pos: 0 };
+        let rev_lines = ReverseLines::with_range(HUGE_SIZE - 9, HUGE_SIZE, reader).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["xyz", "xyz", ""]);
+    }
+
+    // Reader which fails once each time it is told to
+    struct FlakyReader<R> {
+        inner: R,
+        fail: Rc<Cell<bool>>,
+    }
+
+    impl<R: Seek> Seek for FlakyReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    impl<R: Read> Read for FlakyReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            if self.fail.replace(false) {
+                return Err(Error::other("transient error"));
+            }
+
+            self.inner.read(buf)
+        }
+    }
+
+    #[test]
+    fn it_resumes_after_errors_when_not_fused() {
+        let fail = Rc::new(Cell::new(false));
+        let reader = FlakyReader {
+            inner: File::open("tests/crlf_file").unwrap(),
+            fail: Rc::clone(&fail),
+        };
+        let mut rev_lines = ReverseLines::with_capacity(3, reader)
+            .unwrap()
+            .fuse_on_error(false);
 
         assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        // The rest of the line is in the buffer already
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
         assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+
+        // Fail on the next read
+        fail.set(true);
+        assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Io(_))));
         assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
         assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCDEF".to_string());
         assert_matches!(rev_lines.next(), None);
+
+        // By default, iteration stops
+        let reader = FlakyReader {
+            inner: File::open("tests/crlf_file").unwrap(),
+            fail: Rc::clone(&fail),
+        };
+        let mut rev_lines = ReverseLines::with_capacity(3, reader).unwrap();
+
+        fail.set(true);
+        assert_matches!(rev_lines.next(), Some(Err(_)));
+        assert_matches!(rev_lines.next(), None);
     }
 
     #[test]
-    fn it_errors_on_invalid_utf8() {
+    fn it_refreshes_the_end() {
+        let mut rev_lines = ReverseLines::new(Cursor::new(b"ABCDEF\nGHIJK\n".to_vec())).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
+        assert_matches!(rev_lines.refresh_end(), Ok(0));
+
+        // Only the count changes, until the lines are included
+        rev_lines
+            .get_mut()
+            .get_mut()
+            .extend_from_slice(b"LMNOPQRST\n");
+        assert_matches!(rev_lines.refresh_end(), Ok(10));
+        assert_matches!(rev_lines.refresh_end(), Ok(10));
+        assert_eq!(rev_lines.position(), 6);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCDEF".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        assert_matches!(rev_lines.extend_to_end(), Ok(10));
+        assert_eq!(rev_lines.position(), 22);
+        assert_matches!(rev_lines.refresh_end(), Ok(0));
+        assert_matches!(rev_lines.extend_to_end(), Ok(0));
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        rev_lines.get_mut().get_mut().extend_from_slice(b"UVWXYZ\n");
+        assert_matches!(rev_lines.extend_to_end(), Ok(7));
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        // Rewinding reads the lines from before the reader grew too
+        rev_lines.rewind().unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["UVWXYZ", "LMNOPQRST", "GHIJK", "ABCDEF"]);
+    }
+
+    #[test]
+    fn it_reads_older_lines_after_the_reader_grows() {
+        for capacity in 1..=8 {
+            let log = b"ABCDEF\nGHIJK\nLMNOPQRST\n".to_vec();
+            let mut rev_lines = ReverseLines::with_capacity(capacity, Cursor::new(log)).unwrap();
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST");
+
+            rev_lines.get_mut().get_mut().extend_from_slice(b"UVW\n");
+            assert_matches!(rev_lines.extend_to_end(), Ok(4));
+            assert_eq!(rev_lines.size_hint(), (0, Some(17)));
+            rev_lines.get_mut().get_mut().extend_from_slice(b"\nXYZ");
+            assert_matches!(rev_lines.extend_to_end(), Ok(4));
+
+            let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+            assert_eq!(
+                lines,
+                vec!["XYZ", "", "UVW", "GHIJK", "ABCDEF"],
+                "capacity {capacity}"
+            );
+            assert!(rev_lines.at_start());
+        }
+
+        // Lines read from the front stop before those already yielded, then
+        // carry on with those appended
+        let log = b"ABCDEF\nGHIJK\nLMNOPQRST\n".to_vec();
+        let mut rev_lines = ReverseLines::new(Cursor::new(log)).unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST");
+        rev_lines
+            .get_mut()
+            .get_mut()
+            .extend_from_slice(b"UVW\nXYZ\n");
+        assert_matches!(rev_lines.extend_to_end(), Ok(8));
+
+        assert_eq!(rev_lines.next_back().unwrap().unwrap(), "ABCDEF");
+        assert_eq!(rev_lines.next_back().unwrap().unwrap(), "GHIJK");
+        assert_eq!(rev_lines.next_back().unwrap().unwrap(), "UVW");
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "XYZ");
+        assert_matches!(rev_lines.next(), None);
+        assert_matches!(rev_lines.next_back(), None);
+    }
+
+    #[test]
+    fn it_detects_truncation() {
+        let file = b"ABCDEF\nGHIJK\nLMNOPQRST\nUVWXYZ\n".to_vec();
+        let mut rev_lines = ReverseLines::with_capacity(4, Cursor::new(file)).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+        rev_lines.get_mut().get_mut().truncate(16);
+        assert_matches!(rev_lines.refresh_end(), Err(e) if e.kind() == ErrorKind::InvalidData);
+
+        // Reading past the new end fails the same way
+        assert_matches!(
+            rev_lines.next(),
+            Some(Err(ReverseLinesError::Io(e))) if e.to_string() == "reader was truncated"
+        );
+    }
+
+    #[test]
+    fn it_collects_lines() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(
+            rev_lines.collect_lines().unwrap(),
+            vec!["UVWXYZ", "LMNOPQRST", "GHIJK", "ABCDEF"]
+        );
+
+        // Stops on the first error even when not fused
+        let fail = Rc::new(Cell::new(false));
+        let reader = FlakyReader {
+            inner: File::open("tests/multi_line_file").unwrap(),
+            fail: Rc::clone(&fail),
+        };
+        let rev_lines = ReverseLines::new(reader).unwrap().fuse_on_error(false);
+
+        fail.set(true);
+
+        assert_matches!(rev_lines.collect_lines(), Err(ReverseLinesError::Io(_)));
+    }
+
+    #[test]
+    fn it_borrows_lines_where_possible() {
+        let mut rev_lines = ReverseLines::from_bytes(b"a\xff\r\nb\nc\n")
+            .unwrap()
+            .lossy(true);
+
+        assert_matches!(rev_lines.peek_line(), Some(Ok("c")));
+        assert_matches!(rev_lines.next_cow(), Some(Ok(Cow::Owned(line))) if line == "c");
+        assert_matches!(rev_lines.next_cow(), Some(Ok(Cow::Borrowed("b"))));
+        assert_matches!(rev_lines.next_cow(), Some(Ok(Cow::Owned(line))) if line == "a\u{fffd}");
+        assert_matches!(rev_lines.next_cow(), None);
+    }
+
+    #[test]
+    fn it_filters_lines_by_bytes() {
         let file = File::open("tests/invalid_utf8").unwrap();
-        let mut rev_lines = ReverseLines::with_capacity(5, file).unwrap();
+        let rev_lines = ReverseLines::new(file)
+            .unwrap()
+            .with_byte_filter(|line| line.starts_with(b"Valid"));
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
 
-        assert_eq!(rev_lines.next().unwrap().unwrap(), "Valid UTF8".to_string());
-        assert_matches!(rev_lines.next().unwrap(), Err(_));
+        // The line with invalid UTF-8 is never decoded
+        assert_eq!(lines, vec!["Valid UTF8"]);
+
+        let mut rev_lines = ReverseLines::from_str("ERROR a\nb\nERROR c\nd")
+            .unwrap()
+            .with_byte_filter(|line| line.starts_with(b"ERROR"));
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ERROR c".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ERROR a".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        // A peeked line is filtered on its raw bytes too
+        let cursor = Cursor::new(b"a\n\xff\n");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().lossy(true);
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "\u{fffd}");
+        let lines: Vec<String> = rev_lines
+            .with_byte_filter(|line| !line.contains(&0xff))
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(lines, vec!["a"]);
+
+        let mut rev_lines = ReverseLines::from_str("a\nb\n").unwrap();
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "b");
+        let lines: Vec<String> = rev_lines
+            .with_byte_filter(|line| line == b"b")
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(lines, vec!["b"]);
+    }
+
+    #[test]
+    fn it_yields_lines_in_chunks() {
+        let file = File::open("tests/crlf_file").unwrap();
+        let chunks = ReverseLines::new(file).unwrap().chunks(2);
+        let chunks: Vec<Vec<String>> = chunks.map(|chunk| chunk.unwrap()).collect();
+
+        assert_eq!(
+            chunks,
+            vec![
+                vec!["UVWXYZ", ""],
+                vec!["LMNOPQRST", "GHIJK"],
+                vec!["ABCDEF"]
+            ]
+        );
+
+        // Lines before an error are kept for the next chunk
+        let mut chunks = ReverseLines::from_bytes(b"a\nb\n\xff\nc\nd")
+            .unwrap()
+            .chunks(3);
+
+        assert_matches!(chunks.next(), Some(Err(ReverseLinesError::Utf8(_))));
+        assert_eq!(chunks.next().unwrap().unwrap(), vec!["d", "c", "b"]);
+        assert_eq!(chunks.next().unwrap().unwrap(), vec!["a"]);
+        assert_matches!(chunks.next(), None);
+    }
+
+    #[test]
+    fn it_counts_lines_as_they_are_yielded() {
+        let file = File::open("tests/crlf_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(3, file).unwrap();
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "UVWXYZ");
+
+        let mut rev_lines = rev_lines.precount().unwrap();
+        for remaining in (0..5).rev() {
+            assert!(rev_lines.next().unwrap().is_ok());
+            assert_eq!(rev_lines.len(), remaining);
+        }
         assert_matches!(rev_lines.next(), None);
+        assert_eq!(rev_lines.len(), 0);
+
+        // Lines which fail to decode are still yielded
+        let mut rev_lines = ReverseLines::from_bytes(b"a\n\xff\nc")
+            .unwrap()
+            .precount()
+            .unwrap();
+        assert_eq!(rev_lines.len(), 3);
+        assert!(rev_lines.next().unwrap().is_ok());
+        assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Utf8(_))));
+        assert_eq!(rev_lines.len(), 1);
+
+        // A line which failed to be read is read again, unless iteration stops
+        let fail = Rc::new(Cell::new(false));
+        for fuse_on_error in [false, true] {
+            let reader = FlakyReader {
+                inner: File::open("tests/crlf_file").unwrap(),
+                fail: Rc::clone(&fail),
+            };
+            let rev_lines = ReverseLines::with_capacity(3, reader)
+                .unwrap()
+                .fuse_on_error(fuse_on_error);
+            let mut rev_lines = rev_lines.precount().unwrap();
+
+            fail.set(true);
+            assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Io(_))));
+            fail.set(false);
+
+            assert_eq!(rev_lines.len(), rev_lines.by_ref().count());
+        }
+    }
+
+    #[test]
+    fn it_skips_blank_lines() {
+        for capacity in 1..=8 {
+            for keep_ends in [false, true] {
+                let file = File::open("tests/blank_line_file").unwrap();
+                let mut rev_lines = ReverseLines::with_capacity(capacity, file)
+                    .unwrap()
+                    .keep_ends(keep_ends)
+                    .skip_blank();
+
+                let (last, first) = if keep_ends {
+                    ("XYZ\n", "ABCD\n")
+                } else {
+                    ("XYZ", "ABCD")
+                };
+                assert_eq!(rev_lines.next().unwrap().unwrap(), last);
+                assert_eq!(rev_lines.next().unwrap().unwrap(), first);
+                assert_matches!(rev_lines.next(), None);
+            }
+        }
+
+        // Blank once trimmed, with CRLF line endings kept
+        let rev_lines = ReverseLines::from_str("a\r\n \t\r\n\r\nb\n\n")
+            .unwrap()
+            .keep_ends(true)
+            .trim_end(true)
+            .skip_blank();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["b\n", "a\r\n"]);
+    }
+
+    #[test]
+    fn it_reads_lines_as_bytes() {
+        let text = b"ABCDEF\r\nGHIJK\n\xff\nLMNOPQRST\nUVWXYZ";
+        for capacity in 1..=8 {
+            for size in 1..=12 {
+                let rev_lines = ReverseLines::with_capacity(capacity, Cursor::new(text)).unwrap();
+                let mut reader = rev_lines.into_reader();
+
+                let mut reversed = Vec::new();
+                let mut buf = vec![0; size];
+                loop {
+                    match reader.read(&mut buf).unwrap() {
+                        0 => break,
+                        read => reversed.extend_from_slice(&buf[..read]),
+                    }
+                }
+                assert_eq!(reversed, b"UVWXYZ\nLMNOPQRST\n\xff\nGHIJK\nABCDEF\n");
+            }
+        }
+
+        // Line endings are not added when kept, and a peeked line is read
+        let mut rev_lines = ReverseLines::from_str("a\r\nb\r\nc\r\n")
+            .unwrap()
+            .keep_ends(true);
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "c\r\n");
+        let mut reversed = Vec::new();
+        rev_lines.into_reader().read_to_end(&mut reversed).unwrap();
+        assert_eq!(reversed, b"c\r\nb\r\na\r\n");
+
+        // The raw bytes of a peeked line are yielded, not the decoded line
+        let cursor = Cursor::new(b"a\n\xff\n");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().lossy(true);
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "\u{fffd}");
+        let lines: Vec<Vec<u8>> = rev_lines.bytes().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec![b"\xff".to_vec(), b"a".to_vec()]);
+
+        // Errors are returned from reads
+        let reader = ReverseLines::from_str("abc\nd\n").unwrap().max_line_len(2);
+        let mut reader = reader.into_reader();
+        let mut buf = [0; 8];
+        assert_matches!(reader.read(&mut buf), Ok(2));
+        assert_matches!(reader.read(&mut buf), Err(e) if e.kind() == ErrorKind::InvalidData);
+        assert_matches!(reader.read(&mut buf), Ok(0));
+    }
+
+    #[test]
+    fn it_maps_lines() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let lines: Vec<usize> = ReverseLines::new(file)
+            .unwrap()
+            .map_lines(|line| line.len())
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(lines, vec![6, 9, 5, 6]);
+
+        // Errors are passed through without calling the closure
+        let mut calls = 0;
+        let mut rev_lines = ReverseLines::from_bytes(b"1\n\xff\n3")
+            .unwrap()
+            .map_lines(|line| {
+                calls += 1;
+                line.parse::<u32>()
+            });
+        assert_matches!(rev_lines.next(), Some(Ok(Ok(3))));
+        assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Utf8(_))));
+        assert_matches!(rev_lines.next(), Some(Ok(Ok(1))));
+        assert_matches!(rev_lines.next(), None);
+        drop(rev_lines);
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn it_reads_from_memory() {
+        let mut rev_lines = ReverseLines::from_bytes(b"ab\r\n\xffcd\n").unwrap();
+
+        assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Utf8(_))));
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ab".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        let rev_lines = ReverseLines::from_str("").unwrap();
+        assert_eq!(rev_lines.count(), 0);
+
+        let text = String::from("a\nb\n");
+        let lines: Vec<String> = ReverseLines::try_from(text.as_str())
+            .unwrap()
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(lines, vec!["b", "a"]);
+
+        let bytes = text.into_bytes();
+        let mut rev_lines = ReverseLines::try_from(&bytes[..]).unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+    }
+
+    #[test]
+    fn it_reads_files_with_or_without_buffering() {
+        let file = File::open("tests/crlf_file").unwrap();
+        let rev_lines = ReverseLines::with_capacity(4, file).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["UVWXYZ", "", "LMNOPQRST", "GHIJK", "ABCDEF"]);
+
+        let file = File::open("tests/crlf_file").unwrap();
+        let rev_lines = ReverseLines::with_capacity(4, BufReader::with_capacity(3, file)).unwrap();
+        let buffered: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(buffered, lines);
+    }
+
+    #[test]
+    fn it_handles_empty_files() {
+        let file = File::open("tests/empty_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_handles_file_with_one_line() {
+        let file = File::open("tests/one_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCD".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_handles_file_with_multi_lines() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCDEF".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_handles_file_with_blank_lines() {
+        let file = File::open("tests/blank_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "XYZ".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCD".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_handles_file_with_multi_lines_and_with_capacity() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(5, file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCDEF".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_errors_on_invalid_utf8() {
+        let file = File::open("tests/invalid_utf8").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(5, file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "Valid UTF8".to_string());
+        assert_matches!(rev_lines.next().unwrap(), Err(_));
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_continues_past_invalid_utf8() {
+        let text = b"a\n\xff\xfe\nb\n\xc3\nc";
+        for capacity in 1..=8 {
+            let mut rev_lines = ReverseLines::with_capacity(capacity, Cursor::new(text)).unwrap();
+
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "c".to_string());
+            assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Utf8(_))));
+            assert!(!rev_lines.errored());
+            assert_eq!(rev_lines.position(), 6);
+
+            // Iteration resumes from the line before the one which failed
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+            assert_matches!(rev_lines.next(), Some(Err(e)) if e.bytes() == Some(b"\xff\xfe"));
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "a".to_string());
+            assert_matches!(rev_lines.next(), None);
+            assert!(!rev_lines.errored());
+            assert!(rev_lines.at_start());
+        }
+    }
+
+    #[test]
+    fn it_keeps_bytes_of_invalid_utf8() {
+        let file = File::open("tests/invalid_utf8").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        rev_lines.next();
+        let e = rev_lines.next().unwrap().unwrap_err();
+        assert_eq!(
+            e.bytes().unwrap(),
+            b"More valid UTF8\r\0\x9f\x92\x96\0\0\0\0\0\0\0\0\0\0"
+        );
+        assert_matches!(e, ReverseLinesError::Utf8(e) if e.utf8_error().valid_up_to() == 17);
+
+        let cursor = Cursor::new(b"abc\nd");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().max_line_len(1);
+
+        rev_lines.next();
+        assert_matches!(rev_lines.next(), Some(Err(e)) if e.bytes().is_none());
+    }
+
+    #[test]
+    fn it_distinguishes_utf8_errors() {
+        fn last_lines(reader: Cursor<&[u8]>) -> Result<Vec<String>> {
+            let mut lines = Vec::new();
+            for line in ReverseLines::new(reader)? {
+                lines.push(line?);
+            }
+
+            Ok(lines)
+        }
+
+        let cursor = Cursor::new(&b"a\n\xff\nb"[..]);
+        let mut rev_lines = ReverseLines::new(cursor.clone()).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_matches!(
+            rev_lines.next(),
+            Some(Err(ReverseLinesError::Utf8(e))) if e.as_bytes() == b"\xff"
+        );
+
+        let e = last_lines(cursor).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        assert_matches!(e.into_inner(), Some(e) if e.is::<ReverseLinesError>());
+    }
+
+    #[test]
+    fn it_converts_errors_into_io_errors() {
+        let cursor = Cursor::new(&b"a\n\xff\nb"[..]);
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().max_line_len(1);
+
+        rev_lines.next();
+        match rev_lines.next().unwrap().unwrap_err() {
+            e @ ReverseLinesError::Utf8(_) => {
+                assert_eq!(e.into_io_error().kind(), ErrorKind::InvalidData)
+            }
+            e => panic!("unexpected error {e}"),
+        }
+
+        let cursor = Cursor::new(&b"abc\nd"[..]);
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().max_line_len(1);
+
+        rev_lines.next();
+        match rev_lines.next().unwrap().unwrap_err() {
+            ReverseLinesError::Io(e) => assert_eq!(e.to_string(), "line exceeds maximum length"),
+            e => panic!("unexpected error {e}"),
+        }
+    }
+
+    #[test]
+    fn it_handles_custom_delimiter() {
+        let cursor = Cursor::new(b"a\x1eb\x1ec");
+        let mut rev_lines = ReverseLines::with_delimiter(0x1e, cursor).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "c".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_yields_raw_bytes() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(5, file).unwrap().bytes();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), b"UVWXYZ".to_vec());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), b"LMNOPQRST".to_vec());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), b"GHIJK".to_vec());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), b"ABCDEF".to_vec());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_replaces_invalid_utf8_when_lossy() {
+        let file = File::open("tests/invalid_utf8").unwrap();
+        let mut rev_lines = ReverseLines::new_lossy(file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "Valid UTF8".to_string());
+
+        let line = rev_lines.next().unwrap().unwrap();
+        assert!(line.starts_with("More valid UTF8"));
+        assert!(line.contains('\u{FFFD}'));
+
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_handles_multi_byte_delimiter_across_buffers() {
+        let cursor = Cursor::new(b"one||two||three");
+        let mut rev_lines =
+            ReverseLines::with_capacity_and_byte_delimiter(3, b"||", cursor).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "three".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "two".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "one".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_splits_records_on_byte_sequences() {
+        let cases: [(&str, &str); 3] = [
+            ("}\n", "{\"a\": 1}\n{\"b\": {\n}}\n{}\n"),
+            ("---\n", "a--\n---\n----\nb---\n-\n---\n---\nc"),
+            ("\r\n\r\n", "x\r\n\r\ny\r\nz\r\n\r\n"),
+        ];
+
+        for (delimiter, text) in cases {
+            let mut expected: Vec<&str> = text
+                .strip_suffix(delimiter)
+                .unwrap_or(text)
+                .split(delimiter)
+                .collect();
+            expected.reverse();
+
+            for capacity in 1..=9 {
+                let cursor = Cursor::new(text);
+                let rev_lines = ReverseLines::with_capacity_and_byte_delimiter(
+                    capacity,
+                    delimiter.as_bytes(),
+                    cursor,
+                )
+                .unwrap();
+                let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+                assert_eq!(lines, expected, "{delimiter:?} at capacity {capacity}");
+            }
+        }
+
+        // Overlapping occurrences are matched from the end
+        let cursor = Cursor::new(b"x\r\n\r\n\r\ny");
+        let rev_lines = ReverseLines::with_byte_delimiter(b"\r\n\r\n", cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["y", "x\r\n"]);
+    }
+
+    #[test]
+    fn it_trims_line_ends() {
+        let cursor = Cursor::new(b" a \t\r\n\r\nb\x0c  \n  ");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().trim_end(true);
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), " a".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        assert_eq!(rev_lines.next_forward().unwrap().unwrap(), " a".to_string());
+
+        let cursor = Cursor::new(b"a \r\nb\t\n");
+        let rev_lines = ReverseLines::new(cursor)
+            .unwrap()
+            .keep_ends(true)
+            .trim_end(true);
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["b\n", "a\r\n"]);
+    }
+
+    #[test]
+    fn it_trims_both_ends() {
+        let text = "\u{feff} \tA B \r\n\t\n  C\t\r\nD  \n \t ";
+        for cap in 1..=8 {
+            let rev_lines = ReverseLinesBuilder::new()
+                .capacity(cap)
+                .skip_bom(true)
+                .trim(true)
+                .build(Cursor::new(text))
+                .unwrap();
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+            let expected: Vec<String> = text
+                .trim_start_matches('\u{feff}')
+                .lines()
+                .rev()
+                .map(|line| line.trim().to_string())
+                .collect();
+            assert_eq!(lines, expected, "capacity {}", cap);
+            assert_eq!(lines, vec!["", "D", "C", "", "A B"]);
+        }
+
+        // Only the start, and with line endings kept
+        let cursor = Cursor::new(b" a \r\n\t b\t\n");
+        let mut rev_lines = ReverseLines::new(cursor)
+            .unwrap()
+            .keep_ends(true)
+            .trim_start(true);
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b\t\n".to_string());
+        assert_eq!(rev_lines.position(), 4);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a \r\n".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        assert_eq!(
+            rev_lines.next_forward().unwrap().unwrap(),
+            "a \r\n".to_string()
+        );
+        assert_eq!(
+            rev_lines.next_forward().unwrap().unwrap(),
+            "b\t\n".to_string()
+        );
+    }
+
+    #[test]
+    fn it_handles_null_separated_input() {
+        let cursor = Cursor::new(b"a/b\x00c/d\x00");
+        let mut rev_lines = ReverseLines::null_separated(cursor).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "c/d".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a/b".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_keeps_cr_with_custom_delimiter() {
+        let cursor = Cursor::new(b"a\r\x1eb\r\x1e");
+        let mut rev_lines = ReverseLines::with_delimiter(0x1e, cursor).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b\r".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a\r".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_keeps_line_ends() {
+        let cursor = Cursor::new(b"a\r\nb\nc\r\n");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().keep_ends(true);
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "c\r\n".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b\n".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a\r\n".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        let cursor = Cursor::new(b"a\nb");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().keep_ends(true);
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a\n".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_handles_line_endings() {
+        let content = b"a\nb\r\nc\rd";
+
+        let cursor = Cursor::new(content);
+        let rev_lines = ReverseLines::with_line_ending(LineEnding::Lf, cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["c\rd", "b", "a"]);
+
+        let cursor = Cursor::new(content);
+        let rev_lines = ReverseLines::with_line_ending(LineEnding::Cr, cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["d", "\nc", "a\nb"]);
+
+        let cursor = Cursor::new(content);
+        let rev_lines = ReverseLines::with_line_ending(LineEnding::CrLf, cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["c\rd", "a\nb"]);
+
+        let cursor = Cursor::new(content);
+        let rev_lines = ReverseLines::with_line_ending(LineEnding::Any, cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["d", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn it_gives_size_hint() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.size_hint(), (0, Some(30)));
+        rev_lines.next();
+        assert_eq!(rev_lines.size_hint(), (0, Some(23)));
+
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines.len(), 3);
+
+        // Every line may be empty
+        let mut rev_lines = ReverseLines::from_str("\n\n\n").unwrap();
+        assert_eq!(rev_lines.size_hint(), (0, Some(3)));
+        assert_eq!(rev_lines.by_ref().count(), 3);
+        assert_eq!(rev_lines.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn it_counts_lines_like_bufread() {
+        for text in [
+            "", "\n", "\n\n", "\n\n\n", "\r\n\r\n", "a", "a\n", "\na", "\na\n",
+        ] {
+            let mut expected: Vec<String> =
+                text.as_bytes().lines().map(|line| line.unwrap()).collect();
+            expected.reverse();
+
+            for capacity in 1..=4 {
+                let cursor = Cursor::new(text);
+                let rev_lines = ReverseLines::with_capacity(capacity, cursor).unwrap();
+                let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+                assert_eq!(lines, expected, "{text:?}");
+            }
+        }
+
+        assert_eq!(ReverseLines::from_str("\n").unwrap().count(), 1);
+        assert_eq!(ReverseLines::from_str("\n\n").unwrap().count(), 2);
+        assert_eq!(ReverseLines::from_str("\n\n\n").unwrap().count(), 3);
+    }
+
+    #[test]
+    fn it_handles_unicode_line_breaks() {
+        let content = b"a\nb\xe2\x80\xa8c\xe2\x80\xa9d\xe2\x80\xa8";
+
+        let cursor = Cursor::new(content);
+        let rev_lines = ReverseLinesBuilder::new()
+            .capacity(2)
+            .unicode_line_breaks(true)
+            .build(cursor)
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["d", "c", "b", "a"]);
+
+        let cursor = Cursor::new(content);
+        let rev_lines = ReverseLines::with_capacity(2, cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["b\u{2028}c\u{2029}d\u{2028}", "a"]);
+    }
+
+    #[test]
+    fn it_splits_once_on_line_separator_delimiters_with_unicode_line_breaks() {
+        let cursor = Cursor::new("a\u{2028}b\u{2029}c");
+        let rev_lines = ReverseLinesBuilder::new()
+            .byte_delimiter("\u{2028}".as_bytes())
+            .unicode_line_breaks(true)
+            .build(cursor)
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn it_errors_on_lines_over_max_len() {
+        let mut content = b"a\n".to_vec();
+        content.extend_from_slice(&[b'b'; 100]);
+
+        let cursor = Cursor::new(content);
+        let mut rev_lines = ReverseLines::with_capacity(8, cursor)
+            .unwrap()
+            .max_line_len(10);
+
+        assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Io(e))) if e.kind() == ErrorKind::InvalidData);
+        assert_matches!(rev_lines.next(), None);
+
+        // Without any delimiters, the whole reader is one line
+        let reads = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(vec![b'x'; 1 << 20]),
+            reads: Rc::clone(&reads),
+            seeks: Rc::new(Cell::new(0)),
+        };
+        let mut rev_lines = ReverseLines::new(reader).unwrap().max_line_len(1024);
+        reads.set(0);
+
+        assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Io(e))) if e.kind() == ErrorKind::InvalidData);
+        assert_eq!(reads.get(), 1);
+        assert_eq!(rev_lines.position(), 1 << 20);
+
+        let cursor = Cursor::new(b"abc\ndefg");
+        let rev_lines = ReverseLines::new(cursor).unwrap().max_line_len(4);
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["defg", "abc"]);
+    }
+
+    #[test]
+    fn it_round_trips_with_line_ends() {
+        for path in ["tests/multi_line_file", "tests/blank_line_file"] {
+            let file = File::open(path).unwrap();
+            let rev_lines = ReverseLines::with_capacity(3, file)
+                .unwrap()
+                .keep_ends(true);
+            let mut lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+            lines.reverse();
+
+            assert_eq!(lines.concat().into_bytes(), std::fs::read(path).unwrap());
+        }
+    }
+
+    #[test]
+    fn it_yields_invalid_utf8_as_bytes() {
+        let file = File::open("tests/invalid_utf8").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(5, file).unwrap().bytes();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), b"Valid UTF8".to_vec());
+
+        let line = rev_lines.next().unwrap().unwrap();
+        assert!(line.starts_with(b"More valid UTF8\r\0\x9f\x92\x96"));
+        assert!(String::from_utf8(line).is_err());
+
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_reports_position() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.position(), 29);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+        assert_eq!(rev_lines.position(), 22);
+
+        for line in rev_lines.by_ref() {
+            line.unwrap();
+        }
+        assert_eq!(rev_lines.position(), 0);
+
+        let fixtures = [
+            "tests/blank_line_file",
+            "tests/crlf_file",
+            "tests/empty_file",
+            "tests/invalid_utf8",
+            "tests/multi_line_file",
+            "tests/one_line_file",
+        ];
+        for path in fixtures {
+            for capacity in 1..=8 {
+                let file = File::open(path).unwrap();
+                let mut rev_lines = ReverseLines::with_capacity(capacity, file).unwrap();
+
+                let mut last = rev_lines.position();
+                while rev_lines.next().is_some() {
+                    assert!(
+                        rev_lines.position() <= last,
+                        "{path} at capacity {capacity}"
+                    );
+                    last = rev_lines.position();
+                }
+                assert_eq!(rev_lines.position(), 0, "{path} at capacity {capacity}");
+            }
+        }
+    }
+
+    #[test]
+    fn it_reports_how_iteration_ended() {
+        let file = File::open("tests/blank_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(3, file).unwrap();
+
+        assert!(!rev_lines.at_start());
+        assert_eq!(rev_lines.by_ref().count(), 5);
+        assert!(rev_lines.at_start());
+        assert!(!rev_lines.errored());
+
+        // An empty first line is still to be read at the start of the reader
+        let mut rev_lines = ReverseLines::from_str("\na").unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a");
+        assert_eq!(rev_lines.position(), 0);
+        assert!(!rev_lines.at_start());
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "");
+        assert!(!rev_lines.at_start());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "");
+        assert!(rev_lines.at_start());
+
+        let mut rev_lines = ReverseLines::from_str("").unwrap().max_line_len(1);
+        assert!(rev_lines.at_start());
+        assert_matches!(rev_lines.next(), None);
+
+        // An I/O error stops iteration early
+        let mut rev_lines = ReverseLines::from_str("abc\nd\n").unwrap().max_line_len(2);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "d");
+        assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Io(_))));
+        assert_matches!(rev_lines.next(), None);
+        assert!(rev_lines.errored());
+        assert!(!rev_lines.at_start());
+
+        rev_lines.rewind().unwrap();
+        assert!(!rev_lines.errored());
+
+        // Lines which fail to decode do not
+        let mut rev_lines = ReverseLines::from_bytes(b"\xff").unwrap();
+        assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Utf8(_))));
+        assert!(!rev_lines.errored());
+        assert!(rev_lines.at_start());
+    }
+
+    #[test]
+    fn it_returns_inner_reader() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        let mut file = rev_lines.into_inner();
+        assert_eq!(file.stream_position().unwrap(), 22);
+    }
+
+    #[test]
+    fn it_continues_past_invalid_utf8_when_lossy() {
+        let file = File::open("tests/invalid_utf8").unwrap();
+        let rev_lines = ReverseLines::with_line_ending(LineEnding::Any, file)
+            .unwrap()
+            .lossy(true);
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "Valid UTF8");
+        assert!(lines[1].contains('\u{FFFD}'));
+        assert_eq!(lines[2], "More valid UTF8");
+    }
+
+    #[test]
+    fn it_gives_access_to_inner_reader() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.get_ref().metadata().unwrap().len(), 30);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        let position = rev_lines.position();
+        assert_eq!(rev_lines.get_mut().stream_position().unwrap(), position);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+    }
+
+    #[test]
+    fn it_reads_lines_into_buffer() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(5, file).unwrap();
+        let mut buf = String::from("leftover");
+
+        assert_eq!(rev_lines.read_line_into(&mut buf).unwrap(), Some(6));
+        assert_eq!(buf, "UVWXYZ");
+        assert_eq!(rev_lines.read_line_into(&mut buf).unwrap(), Some(9));
+        assert_eq!(buf, "LMNOPQRST");
+        assert_eq!(rev_lines.read_line_into(&mut buf).unwrap(), Some(5));
+        assert_eq!(buf, "GHIJK");
+        assert_eq!(rev_lines.read_line_into(&mut buf).unwrap(), Some(6));
+        assert_eq!(buf, "ABCDEF");
+        assert_eq!(rev_lines.read_line_into(&mut buf).unwrap(), None);
+
+        let file = File::open("tests/invalid_utf8").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.read_line_into(&mut buf).unwrap(), Some(10));
+        assert_matches!(
+            rev_lines.read_line_into(&mut buf),
+            Err(ReverseLinesError::Utf8(_))
+        );
+    }
+
+    #[test]
+    fn it_builds_with_options() {
+        let cursor = Cursor::new(b"one||two||three||");
+        let mut rev_lines = ReverseLinesBuilder::new()
+            .capacity(3)
+            .byte_delimiter(b"||")
+            .keep_ends(true)
+            .max_line_len(5)
+            .build(cursor)
+            .unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "three||".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "two||".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "one||".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        let cursor = Cursor::new(b"");
+        let result = ReverseLinesBuilder::new().byte_delimiter(b"").build(cursor);
+        assert_matches!(result.err(), Some(e) if e.kind() == ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn it_yields_line_offsets() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, file).unwrap().with_offsets();
+
+        assert_eq!(
+            rev_lines.next().unwrap().unwrap(),
+            (23, "UVWXYZ".to_string())
+        );
+        assert_eq!(
+            rev_lines.next().unwrap().unwrap(),
+            (13, "LMNOPQRST".to_string())
+        );
+        assert_eq!(rev_lines.next().unwrap().unwrap(), (7, "GHIJK".to_string()));
+        assert_eq!(
+            rev_lines.next().unwrap().unwrap(),
+            (0, "ABCDEF".to_string())
+        );
+        assert_matches!(rev_lines.next(), None);
+
+        let cursor = Cursor::new(b"ab\r\ncd\r\n");
+        let rev_lines = ReverseLines::new(cursor).unwrap().with_offsets();
+        let lines: Vec<(u64, String)> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec![(4, "cd".to_string()), (0, "ab".to_string())]);
+    }
+
+    #[test]
+    fn it_yields_offsets_usable_for_random_access() {
+        let mut file = File::open("tests/multi_line_file").unwrap();
+        let rev_lines = ReverseLines::with_range(7, 30, &file)
+            .unwrap()
+            .with_offsets();
+        let lines: Vec<(u64, String)> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines.len(), 3);
+
+        for (offset, line) in lines {
+            let mut buf = vec![0; line.len()];
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            file.read_exact(&mut buf).unwrap();
+
+            assert_eq!(buf, line.as_bytes());
+        }
+    }
+
+    #[test]
+    fn it_reads_from_both_ends() {
+        for path in ["tests/multi_line_file", "tests/crlf_file"] {
+            for line_ending in [LineEnding::Lf, LineEnding::Any] {
+                for capacity in 1..=8 {
+                    let build = || {
+                        ReverseLinesBuilder::new()
+                            .capacity(capacity)
+                            .line_ending(line_ending)
+                            .keep_ends(true)
+                            .build(File::open(path).unwrap())
+                            .unwrap()
+                    };
+
+                    let mut expected: Vec<String> = build().map(|line| line.unwrap()).collect();
+                    expected.reverse();
+
+                    // Alternate ends, so that no line is yielded twice
+                    let mut rev_lines = build();
+                    let mut front = Vec::new();
+                    let mut back = Vec::new();
+                    while let Some(line) = rev_lines.next_back() {
+                        front.push(line.unwrap());
+                        match rev_lines.next() {
+                            Some(line) => back.push(line.unwrap()),
+                            None => break,
+                        }
+                    }
+
+                    assert_matches!(rev_lines.next(), None);
+                    assert_matches!(rev_lines.next_back(), None);
+
+                    back.reverse();
+                    front.append(&mut back);
+                    assert_eq!(front, expected);
+                }
+            }
+        }
+
+        let cursor = Cursor::new(b"a\r\nb");
+        let mut rev_lines = ReverseLines::with_line_ending(LineEnding::Any, cursor)
+            .unwrap()
+            .keep_ends(true);
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_eq!(rev_lines.next_back().unwrap().unwrap(), "a\r\n".to_string());
+        assert_matches!(rev_lines.next_back(), None);
+        assert_matches!(rev_lines.next(), None);
+
+        let cursor = Cursor::new(b"a\n\nb\n");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap().rev();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_finds_the_last_matching_line() {
+        let file = File::open("tests/blank_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        let line = rev_lines.find_last(|line| !line.is_empty()).unwrap();
+        assert_eq!(line, Some("XYZ".to_string()));
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
+
+        assert_matches!(rev_lines.find_last(|line| line.contains('Q')), Ok(None));
+        assert_matches!(rev_lines.next(), None);
+
+        let mut rev_lines = ReverseLines::from_bytes(b"ab\n\xff\nc").unwrap();
+        assert_matches!(
+            rev_lines.find_last(|line| line == "ab"),
+            Err(ReverseLinesError::Utf8(_))
+        );
+    }
+
+    #[test]
+    fn it_jumps_to_lines_with_an_index() {
+        for capacity in 1..=8 {
+            let file = File::open("tests/crlf_file").unwrap();
+            let mut rev_lines = ReverseLines::with_capacity(capacity, file).unwrap();
+            let index = rev_lines.line_index().unwrap();
+
+            let mut lines: Vec<(u64, String)> =
+                ReverseLines::with_capacity(capacity, File::open("tests/crlf_file").unwrap())
+                    .unwrap()
+                    .with_offsets()
+                    .map(|line| line.unwrap())
+                    .collect();
+            lines.reverse();
+
+            assert_eq!(index.len(), lines.len());
+            for (line, (offset, _)) in lines.iter().enumerate() {
+                assert_eq!(index.offset(line), Some(*offset));
+            }
+
+            // Jump around in both directions
+            for line in [2, 0, 4, 1, 3] {
+                rev_lines.seek_to_line(&index, line).unwrap();
+                assert_eq!(rev_lines.next().unwrap().unwrap(), lines[line].1);
+
+                rev_lines.seek_to_line(&index, line).unwrap();
+                match lines.get(line + 1) {
+                    Some((_, next)) => {
+                        assert_eq!(&rev_lines.next_forward().unwrap().unwrap(), next)
+                    }
+                    None => assert_matches!(rev_lines.next_forward(), None),
+                }
+            }
+
+            assert_matches!(rev_lines.seek_to_line(&index, 5), Err(e) if e.kind() == ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn it_seeks_to_lines_from_the_end() {
+        for capacity in 1..=8 {
+            let lines: Vec<String> =
+                ReverseLines::with_capacity(capacity, File::open("tests/crlf_file").unwrap())
+                    .unwrap()
+                    .map(|line| line.unwrap())
+                    .collect();
+
+            let file = File::open("tests/crlf_file").unwrap();
+            let mut rev_lines = ReverseLines::with_capacity(capacity, file).unwrap();
+            rev_lines.next();
+            rev_lines.next();
+
+            for line in [3, 0, 4, 1, 2] {
+                rev_lines.seek_to_line_from_end(line).unwrap();
+                assert_eq!(rev_lines.next().unwrap().unwrap(), lines[line]);
+                assert_eq!(rev_lines.by_ref().count(), lines.len() - line - 1);
+            }
+
+            assert_matches!(rev_lines.seek_to_line_from_end(5), Err(e) if e.kind() == ErrorKind::InvalidInput);
+            assert_matches!(rev_lines.next(), None);
+        }
+    }
+
+    #[test]
+    fn it_counts_lines() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, file).unwrap();
+
+        assert_eq!(rev_lines.count_lines().unwrap(), 4);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "LMNOPQRST");
+        assert_eq!(rev_lines.count_lines().unwrap(), 3);
+        assert_eq!(rev_lines.count(), 3);
+
+        for text in ["", "a", "a\n", "\n\n", "a\r\n\r\nb"] {
+            let mut rev_lines = ReverseLines::from_str(text).unwrap();
+            assert_eq!(rev_lines.count_lines().unwrap(), rev_lines.count());
+        }
+    }
+
+    #[test]
+    fn it_finds_all_line_offsets() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, file).unwrap();
+
+        rev_lines.next();
+        assert_eq!(rev_lines.line_offsets().unwrap(), vec![0, 7, 13, 23]);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        let file = File::open("tests/crlf_file").unwrap();
+        let mut rev_lines = ReverseLines::with_range(8, 36, file).unwrap();
+        assert_eq!(rev_lines.line_offsets().unwrap(), vec![8, 15, 26, 28]);
+
+        let cursor = Cursor::new(b"");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap();
+        assert!(rev_lines.line_offsets().unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_strips_cr_from_two_byte_lines() {
+        let cursor = Cursor::new(b"ab\r\ncd\r\n");
+        let rev_lines = ReverseLines::with_capacity(2, cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["cd", "ab"]);
+    }
+
+    #[test]
+    fn it_skips_bom() {
+        let text = b"\xef\xbb\xbfab\ncd\n";
+
+        let rev_lines = ReverseLines::new(Cursor::new(text)).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["cd", "\u{feff}ab"]);
+
+        let mut rev_lines = ReverseLines::with_capacity(2, Cursor::new(text))
+            .unwrap()
+            .skip_bom(true);
+        let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["cd", "ab"]);
+        assert_eq!(rev_lines.next_forward().unwrap().unwrap(), "ab".to_string());
+
+        let mut rev_lines = ReverseLinesBuilder::new()
+            .skip_bom(true)
+            .build(Cursor::new(b"\xef\xbb\xbf"))
+            .unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "".to_string());
+        assert_matches!(rev_lines.next(), None);
+
+        // Only the start of the reader can have one
+        let rev_lines = ReverseLinesBuilder::new()
+            .skip_bom(true)
+            .range(3, 9)
+            .build(Cursor::new(b"ab\n\xef\xbb\xbfcd\n"))
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["\u{feff}cd"]);
+    }
+
+    #[test]
+    fn it_skips_bom_in_file() {
+        for capacity in 1..=8 {
+            let file = File::open("tests/bom_file").unwrap();
+            let rev_lines = ReverseLines::with_capacity(capacity, file)
+                .unwrap()
+                .skip_bom(true);
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+            assert_eq!(lines, vec!["\u{feff}LMN", "GHIJK", "ABCDEF"]);
+
+            let file = File::open("tests/bom_file").unwrap();
+            let mut rev_lines = ReverseLines::with_capacity(capacity, file)
+                .unwrap()
+                .skip_bom(true);
+            assert_eq!(
+                rev_lines.next_back().unwrap().unwrap(),
+                "ABCDEF".to_string()
+            );
+            assert_eq!(
+                rev_lines.next().unwrap().unwrap(),
+                "\u{feff}LMN".to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn it_numbers_lines_from_end() {
+        let file = File::open("tests/blank_line_file").unwrap();
+        let rev_lines = ReverseLines::new(file).unwrap().enumerate_from_end();
+        let lines: Vec<(usize, String)> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                (1, "".to_string()),
+                (2, "".to_string()),
+                (3, "XYZ".to_string()),
+                (4, "".to_string()),
+                (5, "ABCD".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reads_within_range() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_range(7, 23, file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
+        assert_matches!(rev_lines.next(), None);
+        assert_eq!(rev_lines.position(), 7);
+
+        let file = File::open("tests/multi_line_file").unwrap();
+        let result = ReverseLines::with_range(7, 31, file);
+        assert_matches!(result.err(), Some(e) if e.kind() == ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn it_reads_within_range_at_any_capacity() {
+        for capacity in 1..=8 {
+            let file = File::open("tests/multi_line_file").unwrap();
+            let rev_lines = ReverseLinesBuilder::new()
+                .capacity(capacity)
+                .range(7, 23)
+                .build(file)
+                .unwrap();
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+            assert_eq!(lines, vec!["LMNOPQRST", "GHIJK"]);
+        }
+    }
+
+    #[test]
+    fn it_returns_tail_in_order() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.tail(2).unwrap(), vec!["LMNOPQRST", "UVWXYZ"]);
+
+        let file = File::open("tests/multi_line_file").unwrap();
+        let rev_lines = ReverseLines::with_capacity(4, file).unwrap();
+
+        assert_eq!(
+            rev_lines.tail(3).unwrap(),
+            vec!["GHIJK", "LMNOPQRST", "UVWXYZ"]
+        );
+
+        let file = File::open("tests/multi_line_file").unwrap();
+        let rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.tail(10).unwrap().len(), 4);
+
+        let cursor = Cursor::new(b"a\n\xff\nb\nc");
+        let rev_lines = ReverseLines::new(cursor).unwrap();
+
+        assert_eq!(rev_lines.tail(2).unwrap(), vec!["b", "c"]);
+
+        let cursor = Cursor::new(b"a\n\xff\nb\nc");
+        let rev_lines = ReverseLines::new(cursor).unwrap();
+        let result = rev_lines.tail(3);
+
+        assert_matches!(result, Err(ReverseLinesError::Utf8(_)));
+    }
+
+    #[test]
+    fn it_clones_independent_cursors() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, &file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        // Both share the position of the file, which must not matter
+        let mut cloned = rev_lines.clone();
+
+        assert_eq!(cloned.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+        assert_eq!(cloned.next().unwrap().unwrap(), "GHIJK".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+        assert_eq!(cloned.next().unwrap().unwrap(), "ABCDEF".to_string());
+        assert_matches!(cloned.next(), None);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "ABCDEF".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_reads_long_lines() {
+        let lines: Vec<String> = (0..20)
+            .map(|i| "abcdefghij".repeat(i * 97 % 1000))
+            .collect();
+        let text = lines.join("\r\n");
+
+        for capacity in [1, 7, 4096, 100_000] {
+            let cursor = Cursor::new(text.as_bytes());
+            let rev_lines = ReverseLines::with_capacity(capacity, cursor).unwrap();
+            let mut read: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+            read.reverse();
+
+            assert_eq!(read, lines);
+        }
+    }
+
+    #[test]
+    fn it_peeks_lines() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, file).unwrap();
+
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "UVWXYZ");
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "UVWXYZ");
+        assert_eq!(rev_lines.size_hint().0, 1);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "LMNOPQRST");
+        assert_eq!(rev_lines.skip_lines(2).unwrap(), 2);
+
+        assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "ABCDEF");
+        let mut buf = String::new();
+        assert_eq!(rev_lines.read_line_into(&mut buf).unwrap(), Some(6));
+        assert_eq!(buf, "ABCDEF");
+
+        assert_matches!(rev_lines.peek_line(), None);
+        assert_eq!(rev_lines.size_hint(), (0, Some(0)));
+        assert_matches!(rev_lines.next(), None);
+
+        // Errors are returned by the peek, and are not kept
+        let cursor = Cursor::new(b"a\n\xff\nb");
+        let mut rev_lines = ReverseLines::new(cursor).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_matches!(rev_lines.peek_line(), Some(Err(ReverseLinesError::Utf8(_))));
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a".to_string());
+    }
+
+    #[test]
+    fn it_merges_by_peeking() {
+        // Timestamps in descending order when read in reverse
+        let mut logs = [
+            ReverseLines::from_str("01 a\n04 b\n05 c\n").unwrap(),
+            ReverseLines::from_str("02 d\n03 e\n06 f\n").unwrap(),
+        ];
+
+        let mut merged = Vec::new();
+        loop {
+            let latest = logs
+                .iter_mut()
+                .enumerate()
+                .filter_map(|(idx, log)| Some((log.peek_line()?.unwrap().to_string(), idx)))
+                .max();
+
+            match latest {
+                Some((_, idx)) => merged.push(logs[idx].next().unwrap().unwrap()),
+                None => break,
+            }
+        }
+
+        assert_eq!(merged, vec!["06 f", "05 c", "04 b", "03 e", "02 d", "01 a"]);
+    }
+
+    #[test]
+    fn it_numbers_lines_from_top() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        let rev_lines = rev_lines.lines_with_numbers().unwrap();
+        let lines: Vec<(usize, String)> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                (3, "LMNOPQRST".to_string()),
+                (2, "GHIJK".to_string()),
+                (1, "ABCDEF".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_builds_with_capacity_and_delimiter() {
+        let cursor = Cursor::new(b"a;\xff;c");
+        let rev_lines = ReverseLinesBuilder::new()
+            .capacity(1)
+            .delimiter(b';')
+            .lossy(true)
+            .keep_ends(true)
+            .build(cursor)
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["c", "\u{FFFD};", "a;"]);
+    }
+
+    #[test]
+    fn it_formats_with_debug() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(
+            format!("{:?}", rev_lines),
+            "ReverseLines { reader_pos: 29, buf_size: 4096, is_error: false, .. }"
+        );
+    }
+
+    #[test]
+    fn it_hands_off_reader_after_partial_scan() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+
+        // The reader continues from where iteration left off
+        let mut file = rev_lines.into_inner();
+        let mut rest = String::new();
+        file.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "\nLMNOPQRST\nUVWXYZ\n");
+    }
+
+    #[test]
+    fn it_handles_cr_only_line_endings() {
+        let content = b"ABCD\r\rXYZ\r";
+
+        let cursor = Cursor::new(content);
+        let rev_lines = ReverseLines::with_capacity_and_delimiter(2, b'\r', cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["XYZ", "", "ABCD"]);
+
+        let cursor = Cursor::new(content);
+        let rev_lines =
+            ReverseLines::with_capacity_and_line_ending(2, LineEnding::Cr, cursor).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["XYZ", "", "ABCD"]);
+    }
+
+    #[test]
+    fn it_reads_only_the_tail() {
+        let tail = |n: u64, capacity: usize, skip_partial_line: bool| {
+            let lowest = Rc::new(Cell::new(u64::MAX));
+            let reader = TrackingReader {
+                inner: File::open("tests/multi_line_file").unwrap(),
+                lowest: lowest.clone(),
+            };
+            let rev_lines = ReverseLinesBuilder::new()
+                .capacity(capacity)
+                .tail_bytes(n)
+                .skip_partial_line(skip_partial_line)
+                .build(reader)
+                .unwrap();
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+            (lines, lowest.get())
+        };
+
+        for capacity in 1..=8 {
+            // Cut off in the middle of "LMNOPQRST"
+            let (lines, lowest) = tail(11, capacity, false);
+            assert_eq!(lines, vec!["UVWXYZ", "RST"]);
+            assert_eq!(lowest, 19);
+
+            let (lines, lowest) = tail(11, capacity, true);
+            assert_eq!(lines, vec!["UVWXYZ"]);
+            assert_eq!(lowest, 18);
+
+            // Starting on a line boundary
+            for skip_partial_line in [false, true] {
+                let (lines, _) = tail(7, capacity, skip_partial_line);
+                assert_eq!(lines, vec!["UVWXYZ"]);
+
+                let (lines, _) = tail(100, capacity, skip_partial_line);
+                assert_eq!(lines, vec!["UVWXYZ", "LMNOPQRST", "GHIJK", "ABCDEF"]);
+
+                let (lines, _) = tail(0, capacity, skip_partial_line);
+                assert!(lines.is_empty());
+            }
+
+            // All within one line
+            assert_eq!(tail(3, capacity, false).0, vec!["YZ"]);
+            assert!(tail(3, capacity, true).0.is_empty());
+        }
+
+        // A line ending straddling the start is skipped along with the line
+        let cursor = Cursor::new(b"ab\r\ncd\r\nef");
+        let rev_lines = ReverseLinesBuilder::new()
+            .tail_bytes(8)
+            .skip_partial_line(true)
+            .build(cursor)
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["ef", "cd"]);
+
+        // Within a range, and reading from the front
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLinesBuilder::new()
+            .range(0, 23)
+            .tail_bytes(17)
+            .skip_partial_line(true)
+            .build(file)
+            .unwrap();
+        assert_eq!(rev_lines.next_back().unwrap().unwrap(), "GHIJK");
+        assert_eq!(rev_lines.next_back().unwrap().unwrap(), "LMNOPQRST");
+        assert_matches!(rev_lines.next_back(), None);
+
+        // Lines appended later are still read
+        let cursor = Cursor::new(b"ABCDEF\nGHIJK\n".to_vec());
+        let mut rev_lines = ReverseLines::with_tail_bytes(3, cursor).unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "JK");
+        rev_lines.get_mut().get_mut().extend_from_slice(b"LMN\n");
+        assert_matches!(rev_lines.extend_to_end(), Ok(4));
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMN");
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[test]
+    fn it_takes_lines_without_reading_earlier_ones() {
+        let lowest = Rc::new(Cell::new(u64::MAX));
+        let reader = TrackingReader {
+            inner: File::open("tests/multi_line_file").unwrap(),
+            lowest: lowest.clone(),
+        };
+        let rev_lines = ReverseLines::with_capacity(4, reader)
+            .unwrap()
+            .take_lines(2);
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["UVWXYZ", "LMNOPQRST"]);
+
+        // Only the buffer holding the newline before "LMNOPQRST" reaches back
+        // into "GHIJK", and nothing before that is read
+        assert!(lowest.get() >= 7);
+    }
+
+    #[test]
+    fn it_skips_lines() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, file).unwrap();
+
+        assert_eq!(rev_lines.skip_lines(2).unwrap(), 2);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
+
+        assert_eq!(rev_lines.skip_lines(5).unwrap(), 1);
+        assert_matches!(rev_lines.next(), None);
+        assert_eq!(rev_lines.skip_lines(1).unwrap(), 0);
+
+        let cursor = Cursor::new(b"one||two||three");
+        let mut rev_lines =
+            ReverseLines::with_capacity_and_byte_delimiter(2, b"||", cursor).unwrap();
+
+        assert_eq!(rev_lines.skip_lines(1).unwrap(), 1);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "two".to_string());
+    }
+
+    #[test]
+    fn it_shrinks_size_hint_as_lines_are_consumed() {
+        let file = File::open("tests/blank_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(3, file).unwrap();
+        let mut upper = rev_lines.size_hint().1.unwrap();
+
+        while rev_lines.next().is_some() {
+            let (lower, next_upper) = rev_lines.size_hint();
+
+            assert_eq!(lower, 0);
+            assert!(next_upper.unwrap() < upper);
+            upper = next_upper.unwrap();
+        }
+
+        assert_eq!(rev_lines.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn it_strips_crlf_at_any_capacity() {
+        for cap in 1..=8 {
+            let file = File::open("tests/crlf_file").unwrap();
+            let rev_lines = ReverseLines::with_capacity(cap, file).unwrap();
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+            assert!(lines.iter().all(|line| !line.contains('\r')));
+            assert_eq!(
+                lines,
+                vec!["UVWXYZ", "", "LMNOPQRST", "GHIJK", "ABCDEF"],
+                "capacity {}",
+                cap
+            );
+        }
+    }
+
+    #[test]
+    fn it_strips_mixed_line_endings() {
+        let expected = vec!["UVWXYZ", "", "OPQRST", "", "", "LMN", "GHIJK", "ABCDEF"];
+
+        for cap in 1..=12 {
+            let file = File::open("tests/mixed_file").unwrap();
+            let rev_lines = ReverseLines::with_capacity(cap, file).unwrap();
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+            assert!(lines.iter().all(|line| !line.contains('\r')));
+            assert_eq!(lines, expected, "capacity {}", cap);
+
+            // Reading from the front finds the same lines
+            let file = File::open("tests/mixed_file").unwrap();
+            let rev_lines = ReverseLines::with_capacity(cap, file).unwrap();
+            let mut lines: Vec<String> = rev_lines.rev().map(|line| line.unwrap()).collect();
+            lines.reverse();
+            assert_eq!(lines, expected, "capacity {}", cap);
+        }
+
+        // `BufRead::lines` finds the same lines too, once their CRs are
+        // stripped
+        let file = File::open("tests/mixed_file").unwrap();
+        let mut lines: Vec<String> = BufReader::new(file)
+            .lines()
+            .map(|line| line.unwrap().trim_end_matches('\r').to_string())
+            .collect();
+        lines.reverse();
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn it_strips_crlf_across_buffers() {
+        for cap in 1..=6 {
+            let cursor = Cursor::new(b"ab\r\ncde\r\nf\r\n");
+            let rev_lines = ReverseLines::with_capacity(cap, cursor)
+                .unwrap()
+                .with_offsets();
+            let lines: Vec<(u64, String)> = rev_lines.map(|line| line.unwrap()).collect();
+
+            assert_eq!(
+                lines,
+                vec![
+                    (9, "f".to_string()),
+                    (4, "cde".to_string()),
+                    (0, "ab".to_string()),
+                ],
+                "capacity {}",
+                cap
+            );
+        }
+    }
+
+    #[test]
+    fn it_starts_from_offset() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let rev_lines = ReverseLines::with_start_offset(13, file).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["GHIJK", "ABCDEF"]);
+
+        let file = File::open("tests/multi_line_file").unwrap();
+        let rev_lines = ReverseLines::with_start_offset(16, file).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["LMN", "GHIJK", "ABCDEF"]);
+    }
+
+    #[test]
+    fn it_resumes_from_position() {
+        for path in ["tests/multi_line_file", "tests/crlf_file"] {
+            for capacity in 1..=8 {
+                let lines: Vec<String> =
+                    ReverseLines::with_capacity(capacity, File::open(path).unwrap())
+                        .unwrap()
+                        .map(|line| line.unwrap())
+                        .collect();
+
+                for read in 0..=lines.len() {
+                    let file = File::open(path).unwrap();
+                    let mut rev_lines = ReverseLines::with_capacity(capacity, file).unwrap();
+                    for _ in 0..read {
+                        rev_lines.next().unwrap().unwrap();
+                    }
+
+                    let file = File::open(path).unwrap();
+                    let resumed =
+                        ReverseLines::with_start_offset(rev_lines.position(), file).unwrap();
+                    let rest: Vec<String> = resumed.map(|line| line.unwrap()).collect();
+                    assert_eq!(
+                        rest,
+                        lines[read..],
+                        "{path}, capacity {capacity}, read {read}"
+                    );
+                }
+            }
+        }
+
+        // The CR is still part of the line ending after rewinding
+        let cursor = Cursor::new(b"a\r\nb\r\n");
+        let mut rev_lines = ReverseLines::with_start_offset(5, cursor).unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b");
+        rev_lines.rewind().unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["b", "a"]);
+    }
+
+    #[test]
+    fn it_reads_forward_from_position() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_capacity(4, file).unwrap();
+
+        assert_matches!(rev_lines.next_forward(), None);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "LMNOPQRST".to_string());
+        assert_eq!(
+            rev_lines.next_forward().unwrap().unwrap(),
+            "LMNOPQRST".to_string()
+        );
+        assert_eq!(
+            rev_lines.next_forward().unwrap().unwrap(),
+            "UVWXYZ".to_string()
+        );
+        assert_matches!(rev_lines.next_forward(), None);
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ".to_string());
+
+        for line in rev_lines.by_ref() {
+            line.unwrap();
+        }
+
+        assert_eq!(
+            rev_lines.next_forward().unwrap().unwrap(),
+            "ABCDEF".to_string()
+        );
+        assert_eq!(
+            rev_lines.next_forward().unwrap().unwrap(),
+            "GHIJK".to_string()
+        );
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK".to_string());
+    }
+
+    #[test]
+    fn it_reads_forward_with_line_endings() {
+        for line_ending in [LineEnding::Lf, LineEnding::Any] {
+            let cursor = Cursor::new(b"ab\r\ncd\r\n\r\nef");
+            let mut rev_lines =
+                ReverseLines::with_capacity_and_line_ending(3, line_ending, cursor).unwrap();
+
+            for line in rev_lines.by_ref() {
+                line.unwrap();
+            }
+
+            let mut lines = Vec::new();
+            while let Some(line) = rev_lines.next_forward() {
+                lines.push(line.unwrap());
+            }
+            assert_eq!(lines, vec!["ab", "cd", "", "ef"]);
+
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+            assert_eq!(lines, vec!["ef", "", "cd", "ab"]);
+        }
+    }
+
+    #[cfg(feature = "encoding")]
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn it_reads_the_tail_of_utf16() {
+        // The tail starts on a character boundary
+        let text = utf16le("ab\ncd\nef");
+        for n in 6..=7 {
+            let rev_lines = ReverseLinesBuilder::new()
+                .encoding(encoding_rs::UTF_16LE)
+                .tail_bytes(n)
+                .build(Cursor::new(&text))
+                .unwrap();
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+            assert_eq!(lines, vec!["ef", ""]);
+        }
+
+        let rev_lines = ReverseLinesBuilder::new()
+            .encoding(encoding_rs::UTF_16LE)
+            .tail_bytes(9)
+            .skip_partial_line(true)
+            .build(Cursor::new(&text))
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["ef"]);
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn it_reads_utf16() {
+        // U+0A41 U+4100 contains the bytes of U+000A, but not aligned
+        let text = "ab\r\n\u{0a41}\u{4100}\r\nz\n";
+
+        for capacity in 1..=8 {
+            let mut rev_lines = ReverseLinesBuilder::new()
+                .capacity(capacity)
+                .encoding(encoding_rs::UTF_16LE)
+                .build(Cursor::new(utf16le(text)))
+                .unwrap();
+
+            let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+            assert_eq!(lines, vec!["z", "\u{0a41}\u{4100}", "ab"]);
+
+            let mut lines = Vec::new();
+            while let Some(line) = rev_lines.next_forward() {
+                lines.push(line.unwrap());
+            }
+            assert_eq!(lines, vec!["ab", "\u{0a41}\u{4100}", "z"]);
+        }
+
+        let text: Vec<u8> = "a\u{2028}b\nc"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+        let rev_lines = ReverseLinesBuilder::new()
+            .encoding(encoding_rs::UTF_16BE)
+            .unicode_line_breaks(true)
+            .keep_ends(true)
+            .build(Cursor::new(text))
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["c", "b\n", "a\u{2028}"]);
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn it_trims_utf16_line_ends() {
+        // U+2020 is made of the bytes of two spaces
+        let mut rev_lines = ReverseLinesBuilder::new()
+            .encoding(encoding_rs::UTF_16LE)
+            .trim_end(true)
+            .build(Cursor::new(utf16le("a\u{2020} \t\nb")))
+            .unwrap();
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "b".to_string());
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a\u{2020}".to_string());
+        assert_matches!(rev_lines.next(), None);
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn it_skips_blank_utf16_lines() {
+        for peek in [false, true] {
+            let mut rev_lines = ReverseLinesBuilder::new()
+                .encoding(encoding_rs::UTF_16LE)
+                .keep_ends(true)
+                .build(Cursor::new(utf16le("a\n\n")))
+                .unwrap();
+            if peek {
+                assert_eq!(rev_lines.peek_line().unwrap().unwrap(), "\n");
+            }
+
+            let lines: Vec<String> = rev_lines.skip_blank().map(|line| line.unwrap()).collect();
+            assert_eq!(lines, vec!["a\n"], "peek {peek}");
+        }
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn it_reads_legacy_encodings() {
+        let cursor = Cursor::new(b"caf\xe9\r\nna\xefve\r\n");
+        let rev_lines = ReverseLinesBuilder::new()
+            .encoding(encoding_rs::WINDOWS_1252)
+            .build(cursor)
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["na\u{ef}ve", "caf\u{e9}"]);
+
+        // Trail bytes in Shift_JIS may look like ASCII, so only whole lines
+        // are decoded
+        let text = "\u{8868}\n\u{30bd}\n";
+        let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode(text);
+        assert_eq!(&bytes[..], b"\x95\x5c\n\x83\x5c\n");
+        for capacity in 1..=4 {
+            let rev_lines = ReverseLinesBuilder::new()
+                .capacity(capacity)
+                .encoding(encoding_rs::SHIFT_JIS)
+                .build(Cursor::new(&bytes[..]))
+                .unwrap();
+            let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+            assert_eq!(lines, vec!["\u{30bd}", "\u{8868}"]);
+        }
+
+        // Nor may delimiters which could match a trail byte
+        for (encoding, text) in [
+            (encoding_rs::SHIFT_JIS, "\u{8868}\\x"),
+            (encoding_rs::BIG5, "\u{529f}\\x"),
+        ] {
+            let (bytes, _, _) = encoding.encode(text);
+            assert_eq!(bytes[1], b'\\');
+            let result = ReverseLinesBuilder::new()
+                .byte_delimiter(b"\\")
+                .encoding(encoding)
+                .build(Cursor::new(&bytes[..]));
+            assert_matches!(result.err(), Some(e) if e.kind() == ErrorKind::InvalidInput);
+        }
+        let result = ReverseLinesBuilder::new()
+            .byte_delimiter(b"1")
+            .encoding(encoding_rs::GB18030)
+            .build(Cursor::new(b""));
+        assert_matches!(result.err(), Some(e) if e.kind() == ErrorKind::InvalidInput);
+        let cursor = Cursor::new(b"a|b");
+        let rev_lines = ReverseLinesBuilder::new()
+            .byte_delimiter(b"|")
+            .encoding(encoding_rs::WINDOWS_1252)
+            .build(cursor)
+            .unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["b", "a"]);
+
+        let cursor = Cursor::new(b"\x81\n");
+        let mut rev_lines = ReverseLines::with_encoding(encoding_rs::SHIFT_JIS, cursor).unwrap();
+        assert_matches!(
+            rev_lines.next(),
+            Some(Err(ReverseLinesError::Encoding { encoding, bytes }))
+                if encoding == encoding_rs::SHIFT_JIS && bytes == b"\x81"
+        );
+
+        let cursor = Cursor::new(b"a\nb");
+        let result = ReverseLinesBuilder::new()
+            .encoding(encoding_rs::WINDOWS_1252)
+            .unicode_line_breaks(true)
+            .build(cursor);
+        assert_matches!(result.err(), Some(e) if e.kind() == ErrorKind::InvalidInput);
+
+        let cursor = Cursor::new(b"a\nb");
+        let result = ReverseLinesBuilder::new()
+            .encoding(encoding_rs::ISO_2022_JP)
+            .build(cursor);
+        assert_matches!(result.err(), Some(e) if e.kind() == ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn it_handles_invalid_utf16() {
+        // An unpaired surrogate
+        let mut text = utf16le("a\n");
+        text.extend_from_slice(&[0x00, 0xd8]);
+
+        let mut rev_lines = ReverseLinesBuilder::new()
+            .encoding(encoding_rs::UTF_16LE)
+            .build(Cursor::new(text.clone()))
+            .unwrap();
+        assert_matches!(rev_lines.next(), Some(Err(e)) if e.bytes() == Some(&b"\x00\xd8"[..]));
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "a".to_string());
+
+        let mut rev_lines = ReverseLinesBuilder::new()
+            .encoding(encoding_rs::UTF_16LE)
+            .lossy(true)
+            .build(Cursor::new(text))
+            .unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "\u{fffd}".to_string());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn it_streams_lines() {
+        use core::future::poll_fn;
+        use core::pin::Pin;
+        use core::task::{Context, Poll};
+        use futures_core::Stream;
+
+        // Reader which is pending on every other read
+        struct PendingReader {
+            inner: Cursor<&'static [u8]>,
+            pending: bool,
+        }
+
+        impl tokio::io::AsyncSeek for PendingReader {
+            fn start_seek(mut self: Pin<&mut Self>, pos: SeekFrom) -> Result<()> {
+                Pin::new(&mut self.inner).start_seek(pos)
+            }
+
+            fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<u64>> {
+                Pin::new(&mut self.inner).poll_complete(cx)
+            }
+        }
+
+        impl tokio::io::AsyncRead for PendingReader {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut tokio::io::ReadBuf<'_>,
+            ) -> Poll<Result<()>> {
+                self.pending = !self.pending;
+                if self.pending {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+
+                Pin::new(&mut self.inner).poll_read(cx, buf)
+            }
+        }
+
+        let reader = tokio::io::BufReader::new(Cursor::new(b"a\r\n\xff\nb\n"));
+        let rev_lines = AsyncReverseLines::with_capacity(2, reader).await.unwrap();
+        let mut stream = rev_lines.into_stream();
+
+        let mut lines = Vec::new();
+        while let Some(line) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            lines.push(line);
+        }
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].as_ref().unwrap(), "b");
+        assert_matches!(lines[1], Err(ReverseLinesError::Utf8(_)));
+        assert_eq!(lines[2].as_ref().unwrap(), "a");
+
+        let mut rev_lines = stream.into_inner();
+        assert_matches!(rev_lines.next_line().await, None);
+
+        // A line left partway through is carried on with
+        let reader = PendingReader {
+            inner: Cursor::new(b"a\r\n\xff\nb\n"),
+            pending: false,
+        };
+        let rev_lines = AsyncReverseLines::with_capacity(2, reader).await.unwrap();
+        let mut stream = rev_lines.into_stream();
+        let pending = poll_fn(|cx| Poll::Ready(Pin::new(&mut stream).poll_next(cx).is_pending()));
+        assert!(pending.await);
+
+        let mut rev_lines = stream.into_inner();
+        assert_eq!(rev_lines.next_line().await.unwrap().unwrap(), "b");
+        assert_matches!(
+            rev_lines.next_line().await,
+            Some(Err(ReverseLinesError::Utf8(_)))
+        );
+        assert_eq!(rev_lines.next_line().await.unwrap().unwrap(), "a");
+        assert_matches!(rev_lines.next_line().await, None);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn it_reads_lines_asynchronously() {
+        let file = tokio::fs::File::open("tests/multi_line_file")
+            .await
+            .unwrap();
+        let mut rev_lines = AsyncReverseLines::with_capacity(4, file).await.unwrap();
+
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "UVWXYZ".to_string()
+        );
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "LMNOPQRST".to_string()
+        );
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "GHIJK".to_string()
+        );
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "ABCDEF".to_string()
+        );
+        assert_matches!(rev_lines.next_line().await, None);
+
+        let cursor = Cursor::new(b"a\r\n\xff\r\n\r\nb\r\n");
+        let mut rev_lines = AsyncReverseLines::with_capacity(1, cursor).await.unwrap();
+
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "b".to_string()
+        );
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "".to_string()
+        );
+        assert_matches!(
+            rev_lines.next_line().await,
+            Some(Err(ReverseLinesError::Utf8(_)))
+        );
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "a".to_string()
+        );
+        assert_matches!(rev_lines.next_line().await, None);
+
+        let cursor = Cursor::new(b"\r\n\n");
+        let mut rev_lines = AsyncReverseLines::with_capacity(1, cursor).await.unwrap();
+
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "".to_string()
+        );
+        assert_eq!(
+            rev_lines.next_line().await.unwrap().unwrap(),
+            "".to_string()
+        );
+        assert_matches!(rev_lines.next_line().await, None);
+    }
+
+    #[test]
+    fn it_reads_each_buffer_once() {
+        let reads = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(b"a\nb\nc\nd\ne\nf\ng\n"),
+            reads: Rc::clone(&reads),
+            seeks: Rc::new(Cell::new(0)),
+        };
+        let mut rev_lines = ReverseLines::with_capacity(8, reader).unwrap();
+
+        // Reading the trailing newline
+        assert_eq!(reads.get(), 1);
+
+        let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["g", "f", "e", "d", "c", "b", "a"]);
+
+        // The 13 bytes left are read in two buffers
+        assert_eq!(reads.get(), 3);
+
+        let mut rest = String::new();
+        rev_lines.into_inner().read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "a\nb\nc\nd\ne\nf\ng\n");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn it_reads_each_buffer_once_asynchronously() {
+        let reads = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(b"a\nb\nc\nd\ne\nf\ng\n"),
+            reads: Rc::clone(&reads),
+            seeks: Rc::new(Cell::new(0)),
+        };
+        let mut rev_lines = AsyncReverseLines::with_capacity(8, reader).await.unwrap();
+
+        // Reading the trailing newline
+        assert_eq!(reads.get(), 1);
+
+        let mut lines = Vec::new();
+        while let Some(line) = rev_lines.next_line().await {
+            lines.push(line.unwrap());
+        }
+        assert_eq!(lines, vec!["g", "f", "e", "d", "c", "b", "a"]);
+
+        // The 13 bytes left are read in two buffers
+        assert_eq!(reads.get(), 3);
+    }
+
+    #[test]
+    fn it_reads_many_short_lines() {
+        let text: String = (0..10_000).map(|i| format!("{i}\n")).collect();
+        let rev_lines = ReverseLines::with_capacity(64, Cursor::new(text)).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+        let expected: Vec<String> = (0..10_000).rev().map(|i| i.to_string()).collect();
+
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn it_seeks_once_per_buffer() {
+        let seeks = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(format!("{}\n", "x".repeat(40))),
+            reads: Rc::new(Cell::new(0)),
+            seeks: Rc::clone(&seeks),
+        };
+        let mut rev_lines = ReverseLines::with_capacity(4, reader).unwrap();
+        seeks.set(0);
+
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "x".repeat(40));
+
+        // One seek for each of the 10 buffers
+        assert_eq!(seeks.get(), 10);
+
+        // The reader is only moved to the position when handed out
+        let mut reader = rev_lines.into_inner();
+        assert_eq!(seeks.get(), 11);
+        assert_eq!(reader.stream_position().unwrap(), 0);
+
+        // Many lines within one buffer need only one seek between them
+        let seeks = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new("x\n".repeat(20)),
+            reads: Rc::new(Cell::new(0)),
+            seeks: Rc::clone(&seeks),
+        };
+        let mut rev_lines = ReverseLines::with_capacity(64, reader).unwrap();
+        seeks.set(0);
+
+        let lines: Vec<String> = rev_lines.by_ref().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["x"; 20]);
+        assert_eq!(seeks.get(), 1);
+
+        // Moving the reader does not affect iteration
+        rev_lines.rewind().unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "x");
+        rev_lines.get_mut().seek(SeekFrom::Start(7)).unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "x");
+        assert_eq!(rev_lines.position(), 35);
+    }
+
+    #[cfg(feature = "memmap2")]
+    #[test]
+    fn it_reads_memory_maps() {
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.unwrap();
+        let rev_lines = ReverseLines::from_mmap(mmap).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["UVWXYZ", "LMNOPQRST", "GHIJK", "ABCDEF"]);
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn it_reads_gzip_files() {
+        let file = File::open("tests/multi_line_file.gz").unwrap();
+        let rev_lines = ReverseLines::from_gzip(file).unwrap();
+        let lines: Vec<String> = rev_lines.map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec!["UVWXYZ", "LMNOPQRST", "GHIJK", "ABCDEF"]);
+
+        // Members appended to each other are read one after another
+        let gzip = std::fs::read("tests/multi_line_file.gz").unwrap();
+        let rev_lines = ReverseLines::from_gzip(&[&gzip[..], &gzip[..]].concat()[..]).unwrap();
+        assert_eq!(rev_lines.count(), 8);
+
+        let file = File::open("tests/multi_line_file").unwrap();
+        assert_matches!(ReverseLines::from_gzip(file), Err(e) if e.kind() == ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_resumes_from_saved_state() {
+        for capacity in 1..=8 {
+            let lines: Vec<String> =
+                ReverseLines::with_capacity(capacity, File::open("tests/crlf_file").unwrap())
+                    .unwrap()
+                    .map(|line| line.unwrap())
+                    .collect();
+
+            for read in 0..=lines.len() {
+                let file = File::open("tests/crlf_file").unwrap();
+                let mut rev_lines = ReverseLines::with_capacity(capacity, file).unwrap();
+                for _ in 0..read {
+                    rev_lines.next().unwrap().unwrap();
+                }
+
+                let saved = serde_json::to_string(&rev_lines.state()).unwrap();
+                let state: crate::State = serde_json::from_str(&saved).unwrap();
+                assert_eq!(state.position(), rev_lines.position());
+
+                let file = File::open("tests/crlf_file").unwrap();
+                let resumed = ReverseLines::from_state(file, state).unwrap();
+                let rest: Vec<String> = resumed.map(|line| line.unwrap()).collect();
+                assert_eq!(rest, lines[read..], "capacity {capacity}, read {read}");
+            }
+        }
+
+        // Ranges and lines read from the front are kept
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::with_range(0, 22, file).unwrap();
+        assert_eq!(
+            rev_lines.next_back().unwrap().unwrap(),
+            "ABCDEF".to_string()
+        );
+        let state = rev_lines.state();
+
+        let file = File::open("tests/multi_line_file").unwrap();
+        let resumed = ReverseLines::from_state(file, state.clone()).unwrap();
+        let rest: Vec<String> = resumed.map(|line| line.unwrap()).collect();
+        assert_eq!(rest, vec!["LMNOPQRST", "GHIJK"]);
+
+        // As are the lines left on either side of those already yielded when
+        // the reader grew
+        let mut rev_lines = ReverseLines::new(Cursor::new(b"ABCDEF\nGHIJK\n".to_vec())).unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK");
+        rev_lines
+            .get_mut()
+            .get_mut()
+            .extend_from_slice(b"LMN\nOPQ\n");
+        rev_lines.extend_to_end().unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "OPQ");
+        let saved = serde_json::to_string(&rev_lines.state()).unwrap();
+
+        let cursor = Cursor::new(b"ABCDEF\nGHIJK\nLMN\nOPQ\n");
+        let resumed =
+            ReverseLines::from_state(cursor, serde_json::from_str(&saved).unwrap()).unwrap();
+        let rest: Vec<String> = resumed.map(|line| line.unwrap()).collect();
+        assert_eq!(rest, vec!["LMN", "ABCDEF"]);
+
+        // A peeked line is yielded first, rather than skipped
+        let file = File::open("tests/multi_line_file").unwrap();
+        let mut rev_lines = ReverseLines::new(file).unwrap();
+        assert_matches!(rev_lines.peek_line(), Some(Ok("UVWXYZ")));
+        let saved = serde_json::to_string(&rev_lines.state()).unwrap();
+
+        let file = File::open("tests/multi_line_file").unwrap();
+        let resumed =
+            ReverseLines::from_state(file, serde_json::from_str(&saved).unwrap()).unwrap();
+        let rest: Vec<String> = resumed.map(|line| line.unwrap()).collect();
+        assert_eq!(rest, vec!["UVWXYZ", "LMNOPQRST", "GHIJK", "ABCDEF"]);
+
+        let cursor = Cursor::new(&b"ABCDEF\nGHIJK"[..]);
+        assert_matches!(ReverseLines::from_state(cursor, state), Err(e) if e.kind() == ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_rejects_corrupted_states() {
+        let state = |fields: &str| -> crate::State {
+            let mut state = serde_json::json!({
+                "reader_pos": 22,
+                "start": 0,
+                "end": null,
+                "end_pos": 30,
+                "front_pos": 0,
+                "buf_size": 4096,
+                "line_end": [10],
+                "gaps": [],
+                "peeked": null,
+                "is_error": false,
+            });
+            let fields: serde_json::Value = serde_json::from_str(fields).unwrap();
+            for (key, value) in fields.as_object().unwrap() {
+                state[key] = value.clone();
+            }
+            serde_json::from_value(state).unwrap()
+        };
+
+        let file = File::open("tests/multi_line_file").unwrap();
+        let resumed = ReverseLines::from_state(file, state("{}")).unwrap();
+        let rest: Vec<String> = resumed.map(|line| line.unwrap()).collect();
+        assert_eq!(rest, vec!["LMNOPQRST", "GHIJK", "ABCDEF"]);
+
+        for fields in [
+            r#"{"reader_pos": 18446744073709551615}"#,
+            r#"{"front_pos": 23}"#,
+            r#"{"start": 23, "front_pos": 23}"#,
+            r#"{"start": 1}"#,
+            r#"{"end_pos": 22}"#,
+            r#"{"end": 29}"#,
+            r#"{"gaps": [{"start": 23, "end": 24, "line_end": []}]}"#,
+            r#"{"gaps": [{"start": 6, "end": 6, "line_end": [10]}]}"#,
+            r#"{"peeked": [255]}"#,
+        ] {
+            let file = File::open("tests/multi_line_file").unwrap();
+            assert_matches!(
+                ReverseLines::from_state(file, state(fields)),
+                Err(e) if e.kind() == ErrorKind::InvalidData,
+                "{fields}"
+            );
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_tests {
+    use super::*;
+
+    // Reader over bytes in memory, implementing the traits from `io` directly
+    struct SliceReader<'a> {
+        bytes: &'a [u8],
+        pos: u64,
+    }
+
+    impl Seek for SliceReader<'_> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            let pos = match pos {
+                SeekFrom::Start(offset) => Some(offset),
+                SeekFrom::End(offset) => (self.bytes.len() as u64).checked_add_signed(offset),
+                SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+            };
+
+            self.pos = pos.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "bad seek"))?;
+            Ok(self.pos)
+        }
+    }
+
+    impl Read for SliceReader<'_> {
+        fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+            let start = min(self.pos, self.bytes.len() as u64) as usize;
+            let bytes = self
+                .bytes
+                .get(start..start + buf.len())
+                .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "end of slice"))?;
+
+            buf.copy_from_slice(bytes);
+            self.pos += buf.len() as u64;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_reads_without_std() {
+        for capacity in 1..=8 {
+            let reader = SliceReader {
+                bytes: b"ABCDEF\r\nGHIJK\n\n\xffLMN\nUVWXYZ\n",
+                pos: 0,
+            };
+            let mut rev_lines = ReverseLines::with_capacity(capacity, reader).unwrap();
+
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ");
+            assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Utf8(_))));
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "");
+            assert_eq!(rev_lines.next_back().unwrap().unwrap(), "ABCDEF");
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK");
+            assert_matches!(rev_lines.next(), None);
+        }
+    }
+
+    // Reader over bytes in memory, implementing the traits from `embedded_io`,
+    // which fails with `fail` once it is set
+    #[cfg(feature = "embedded-io")]
+    struct EmbeddedReader<'a> {
+        bytes: &'a [u8],
+        pos: u64,
+        fail: Option<embedded_io::ErrorKind>,
+    }
+
+    #[cfg(feature = "embedded-io")]
+    impl embedded_io::ErrorType for EmbeddedReader<'_> {
+        type Error = embedded_io::ErrorKind;
+    }
+
+    #[cfg(feature = "embedded-io")]
+    impl embedded_io::Read for EmbeddedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> result::Result<usize, Self::Error> {
+            if let Some(kind) = self.fail {
+                return Err(kind);
+            }
+
+            let start = min(self.pos, self.bytes.len() as u64) as usize;
+            let size = min(buf.len(), self.bytes.len() - start);
+            buf[..size].copy_from_slice(&self.bytes[start..start + size]);
+            self.pos += size as u64;
+            Ok(size)
+        }
+    }
+
+    #[cfg(feature = "embedded-io")]
+    impl embedded_io::Seek for EmbeddedReader<'_> {
+        fn seek(&mut self, pos: embedded_io::SeekFrom) -> result::Result<u64, Self::Error> {
+            let pos = match pos {
+                embedded_io::SeekFrom::Start(offset) => Some(offset),
+                embedded_io::SeekFrom::End(offset) => {
+                    (self.bytes.len() as u64).checked_add_signed(offset)
+                }
+                embedded_io::SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+            };
+
+            self.pos = pos.ok_or(embedded_io::ErrorKind::InvalidInput)?;
+            Ok(self.pos)
+        }
+    }
+
+    #[cfg(feature = "embedded-io")]
+    #[test]
+    fn it_reads_embedded_io_readers() {
+        for capacity in 1..=8 {
+            let reader = EmbeddedReader {
+                bytes: b"ABCDEF\r\nGHIJK\n\n\xffLMN\nUVWXYZ\n",
+                pos: 0,
+                fail: None,
+            };
+            let mut rev_lines = ReverseLines::with_capacity(capacity, reader).unwrap();
+
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "UVWXYZ");
+            assert_matches!(rev_lines.next(), Some(Err(ReverseLinesError::Utf8(_))));
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "");
+            assert_eq!(rev_lines.next_back().unwrap().unwrap(), "ABCDEF");
+            assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK");
+            assert_matches!(rev_lines.next(), None);
+        }
+
+        // Errors from the reader keep their kind where there is one to match
+        let reader = EmbeddedReader {
+            bytes: b"ABCDEF\nGHIJK\n",
+            pos: 0,
+            fail: None,
+        };
+        let mut rev_lines = ReverseLines::with_capacity(4, reader).unwrap();
+        assert_eq!(rev_lines.next().unwrap().unwrap(), "GHIJK");
+
+        rev_lines.get_mut().fail = Some(embedded_io::ErrorKind::InvalidData);
+        assert_matches!(
+            rev_lines.next(),
+            Some(Err(ReverseLinesError::Io(e))) if e.kind() == ErrorKind::InvalidData
+        );
+
+        rev_lines.get_mut().fail = Some(embedded_io::ErrorKind::Other);
+        assert_matches!(rev_lines.rewind(), Err(e) if e.kind() == ErrorKind::Other);
+
+        // Running out of bytes means the reader was truncated
+        rev_lines.get_mut().fail = None;
+        rev_lines.rewind().unwrap();
+        rev_lines.get_mut().bytes = b"ABC";
+        assert_matches!(
+            rev_lines.next(),
+            Some(Err(ReverseLinesError::Io(e))) if e.kind() == ErrorKind::InvalidData
+        );
     }
 }
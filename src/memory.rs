@@ -0,0 +1,45 @@
+use std::io::{Cursor, Error, Result};
+
+use crate::{ReverseLines, ReverseLinesBuilder};
+
+impl<'a> ReverseLines<Cursor<&'a [u8]>> {
+    /// Create a new `ReverseLines` struct over bytes in memory, with the
+    /// default options.
+    ///
+    /// Other options can be set by passing `Cursor::new(bytes)` to
+    /// [`ReverseLinesBuilder::build`].
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<ReverseLines<Cursor<&'a [u8]>>> {
+        ReverseLinesBuilder::new().build(Cursor::new(bytes))
+    }
+
+    /// Create a new `ReverseLines` struct over a string, with the default
+    /// options.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///
+    ///  let lines = ReverseLines::from_str("a\nb\nc").unwrap().collect::<Result<Vec<_>, _>>();
+    ///  assert_eq!(lines.unwrap(), ["c", "b", "a"]);
+    /// ```
+    // `FromStr` cannot be implemented, as it does not borrow the string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &'a str) -> Result<ReverseLines<Cursor<&'a [u8]>>> {
+        ReverseLines::from_bytes(text.as_bytes())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for ReverseLines<Cursor<&'a [u8]>> {
+    type Error = Error;
+
+    fn try_from(bytes: &'a [u8]) -> Result<ReverseLines<Cursor<&'a [u8]>>> {
+        ReverseLines::from_bytes(bytes)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ReverseLines<Cursor<&'a [u8]>> {
+    type Error = Error;
+
+    fn try_from(text: &'a str) -> Result<ReverseLines<Cursor<&'a [u8]>>> {
+        ReverseLines::from_str(text)
+    }
+}
@@ -0,0 +1,17 @@
+use std::io::{Cursor, Result};
+
+use memmap2::Mmap;
+
+use crate::{ReverseLines, ReverseLinesBuilder};
+
+impl ReverseLines<Cursor<Mmap>> {
+    /// Create a new `ReverseLines` struct over a memory-mapped file, with the
+    /// default options. Lines are copied straight out of the mapping, so no
+    /// system calls are made while iterating.
+    ///
+    /// Other options can be set by passing `Cursor::new(mmap)` to
+    /// [`ReverseLinesBuilder::build`].
+    pub fn from_mmap(mmap: Mmap) -> Result<ReverseLines<Cursor<Mmap>>> {
+        ReverseLinesBuilder::new().build(Cursor::new(mmap))
+    }
+}
@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
+use crate::{truncated, Gap, ReverseLines, ReverseLinesBuilder};
+
+/// `State` struct, holding how far a `ReverseLines` has got so that it can
+/// be saved and resumed later
+///
+/// This is created by [`ReverseLines::state`], and used with
+/// [`ReverseLines::from_state`] or [`ReverseLinesBuilder::build_from_state`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    reader_pos: u64,
+    start: u64,
+    end: Option<u64>,
+    end_pos: u64,
+    front_pos: u64,
+    buf_size: u64,
+    line_end: Vec<u8>,
+    gaps: Vec<Gap>,
+    peeked: Option<Vec<u8>>,
+    is_error: bool,
+}
+
+impl State {
+    /// Return the byte offset into the reader, as returned by
+    /// [`ReverseLines::position`] when the state was saved.
+    pub fn position(&self) -> u64 {
+        self.reader_pos
+    }
+
+    // Check that the positions are in order, as they would be in a state
+    // saved by `ReverseLines::state`, and return the end of the line end
+    fn validate(&self) -> Result<u64> {
+        let line_end_pos = |pos: u64, line_end: &[u8]| {
+            u64::try_from(line_end.len())
+                .ok()
+                .and_then(|len| pos.checked_add(len))
+                .ok_or_else(invalid_state)
+        };
+
+        // Lines already yielded when the reader grew lie between the front
+        // and the position
+        let mut region_start = self.front_pos;
+        for gap in &self.gaps {
+            if region_start > gap.start || line_end_pos(gap.start, &gap.line_end)? > gap.end {
+                return Err(invalid_state());
+            }
+            region_start = gap.end;
+        }
+
+        let line_end_pos = line_end_pos(self.reader_pos, &self.line_end)?;
+        if self.start > self.front_pos
+            || region_start > self.reader_pos
+            || line_end_pos > self.end_pos
+            || self.end.is_some_and(|end| self.end_pos > end)
+        {
+            return Err(invalid_state());
+        }
+
+        Ok(line_end_pos)
+    }
+}
+
+fn invalid_state() -> Error {
+    Error::new(ErrorKind::InvalidData, "invalid saved state")
+}
+
+impl<R: Seek + Read> ReverseLines<R> {
+    /// Save how far iteration has got, including the range being read and
+    /// the buffer capacity, but not the other options. Nothing is read.
+    ///
+    /// A peeked line is saved too, so a resumed `ReverseLines` yields it
+    /// first.
+    pub fn state(&self) -> State {
+        State {
+            reader_pos: self.reader_pos,
+            start: self.start,
+            end: self.end,
+            end_pos: self.end_pos,
+            front_pos: self.front_pos,
+            buf_size: self.buf_size,
+            line_end: self.line_end.clone(),
+            gaps: self.gaps.clone(),
+            // The raw bytes of a peeked line are left in `scratch`
+            peeked: match self.peeked {
+                Some(Some(_)) => Some(self.scratch.clone()),
+                _ => None,
+            },
+            is_error: self.is_error,
+        }
+    }
+
+    /// Create a new `ReverseLines` struct from a `<R>` with the default
+    /// options, resuming from a state saved by [`ReverseLines::state`] so
+    /// that it yields the same lines as the one the state was saved from.
+    ///
+    /// Returns an error of kind `ErrorKind::InvalidData` if the state is
+    /// inconsistent, or the reader has been truncated to before the saved
+    /// position.
+    ///
+    /// ```
+    ///  use reverse_lines::ReverseLines;
+    ///  use std::fs::File;
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::new(file).unwrap();
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "UVWXYZ");
+    ///  let state = reverse_lines.state();
+    ///
+    ///  let file = File::open("tests/multi_line_file").unwrap();
+    ///  let mut reverse_lines = ReverseLines::from_state(file, state).unwrap();
+    ///  assert_eq!(reverse_lines.next().unwrap().unwrap(), "LMNOPQRST");
+    /// ```
+    pub fn from_state(reader: R, state: State) -> Result<ReverseLines<R>> {
+        ReverseLinesBuilder::new().build_from_state(reader, state)
+    }
+}
+
+impl ReverseLinesBuilder {
+    /// Build a `ReverseLines` struct from `reader` with these options,
+    /// resuming from a state saved by [`ReverseLines::state`]. The range and
+    /// capacity are taken from the state, and the other options should match
+    /// those of the `ReverseLines` the state was saved from.
+    ///
+    /// Returns an error as [`ReverseLinesBuilder::build`] would, or of kind
+    /// `ErrorKind::InvalidData` if the state is inconsistent, or the reader
+    /// has been truncated to before the saved position.
+    pub fn build_from_state<R: Seek + Read>(
+        &self,
+        reader: R,
+        state: State,
+    ) -> Result<ReverseLines<R>> {
+        let line_end_pos = state.validate()?;
+        let buf_size = usize::try_from(state.buf_size).map_err(|_| invalid_state())?;
+
+        let mut reverse_lines = self.build_unread(reader, state.start, state.end)?;
+        reverse_lines.set_capacity(buf_size)?;
+
+        let len = reverse_lines.reader.seek(SeekFrom::End(0))?;
+        if len < line_end_pos {
+            return Err(truncated());
+        }
+
+        reverse_lines.reader_pos = state.reader_pos;
+        reverse_lines.end_pos = state.end_pos;
+        reverse_lines.front_pos = state.front_pos;
+        reverse_lines.line_end = state.line_end;
+        reverse_lines.gaps = state.gaps;
+        reverse_lines.is_error = state.is_error;
+        if let Some(peeked) = state.peeked {
+            let line = reverse_lines
+                .decode_slice(&peeked)
+                .map_err(|_| invalid_state())?
+                .into_owned();
+            reverse_lines.peeked = Some(Some(line));
+            reverse_lines.scratch = peeked;
+        }
+
+        Ok(reverse_lines)
+    }
+}